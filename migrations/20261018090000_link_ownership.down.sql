@@ -0,0 +1,3 @@
+DROP INDEX links_created_by_token ON links;
+ALTER TABLE links DROP COLUMN created_by_token;
+ALTER TABLE tokens DROP COLUMN id;
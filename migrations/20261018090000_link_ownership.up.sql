@@ -0,0 +1,3 @@
+ALTER TABLE tokens ADD id BIGINT UNSIGNED not null AUTO_INCREMENT PRIMARY KEY FIRST;
+ALTER TABLE links ADD created_by_token BIGINT UNSIGNED;
+CREATE INDEX links_created_by_token ON links (created_by_token, created_at);
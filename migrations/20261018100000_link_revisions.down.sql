@@ -0,0 +1,2 @@
+drop table link_revisions;
+ALTER TABLE links DROP COLUMN editable;
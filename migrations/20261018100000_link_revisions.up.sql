@@ -0,0 +1,11 @@
+ALTER TABLE links ADD editable BOOLEAN not null default FALSE;
+
+create table if not exists link_revisions (
+    id BIGINT UNSIGNED not null AUTO_INCREMENT PRIMARY KEY,
+    link_id TEXT not null,
+    previous_link TEXT not null,
+    link TEXT not null,
+    created_at TIMESTAMP not null default CURRENT_TIMESTAMP,
+    actor_token BIGINT UNSIGNED
+);
+CREATE INDEX link_revisions_link_id ON link_revisions (link_id(16));
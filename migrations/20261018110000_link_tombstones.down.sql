@@ -0,0 +1,5 @@
+-- Disabled links don't have a hash anymore and it can't be recomputed here, so this fails
+-- with "Invalid use of NULL value" while any link is disabled. Delete or re-enable them first.
+ALTER TABLE links MODIFY hash BLOB not null;
+ALTER TABLE links DROP COLUMN disabled_at;
+drop table link_tombstones;
@@ -0,0 +1,9 @@
+ALTER TABLE links MODIFY hash BLOB;
+ALTER TABLE links ADD disabled_at TIMESTAMP NULL;
+
+create table if not exists link_tombstones (
+    id TEXT not null,
+    deleted_at TIMESTAMP not null default CURRENT_TIMESTAMP,
+    actor_token BIGINT UNSIGNED
+);
+CREATE INDEX link_tombstones_id ON link_tombstones (id(16));
@@ -0,0 +1 @@
+ALTER TABLE links ADD password_hash TEXT;
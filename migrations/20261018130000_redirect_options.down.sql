@@ -0,0 +1,4 @@
+ALTER TABLE links DROP COLUMN cache_max_age;
+ALTER TABLE links DROP COLUMN forward_path;
+ALTER TABLE links DROP COLUMN forward_query;
+ALTER TABLE links DROP COLUMN redirect_code;
@@ -0,0 +1,4 @@
+ALTER TABLE links ADD redirect_code SMALLINT UNSIGNED;
+ALTER TABLE links ADD forward_query BOOLEAN not null default FALSE;
+ALTER TABLE links ADD forward_path BOOLEAN not null default FALSE;
+ALTER TABLE links ADD cache_max_age INT UNSIGNED;
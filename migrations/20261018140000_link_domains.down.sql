@@ -0,0 +1,5 @@
+ALTER TABLE link_tombstones DROP COLUMN domain;
+ALTER TABLE link_revisions DROP COLUMN link_domain;
+ALTER TABLE origins DROP COLUMN domain;
+DROP INDEX links_domain_id ON links;
+ALTER TABLE links DROP COLUMN domain;
@@ -0,0 +1,5 @@
+ALTER TABLE links ADD domain VARCHAR(255) not null default '';
+CREATE INDEX links_domain_id ON links (domain, id(16));
+ALTER TABLE origins ADD domain VARCHAR(255) not null default '';
+ALTER TABLE link_revisions ADD link_domain VARCHAR(255) not null default '';
+ALTER TABLE link_tombstones ADD domain VARCHAR(255) not null default '';
@@ -0,0 +1 @@
+ALTER TABLE links ADD quarantined_at TIMESTAMP NULL;
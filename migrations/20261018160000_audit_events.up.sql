@@ -0,0 +1,11 @@
+create table if not exists audit_events (
+    id BIGINT UNSIGNED not null AUTO_INCREMENT PRIMARY KEY,
+    created_at TIMESTAMP not null default CURRENT_TIMESTAMP,
+    actor_token BIGINT UNSIGNED,
+    action VARCHAR(32) not null,
+    target VARCHAR(255) not null,
+    target_domain VARCHAR(255),
+    client_ip TINYBLOB
+);
+CREATE INDEX audit_events_created_at ON audit_events (created_at);
+CREATE INDEX audit_events_target ON audit_events (target);
@@ -0,0 +1,4 @@
+DROP INDEX links_expires_at ON links;
+ALTER TABLE links DROP COLUMN expires_at;
+DROP INDEX origins_created_at ON origins;
+ALTER TABLE origins DROP COLUMN created_at;
@@ -0,0 +1,6 @@
+ALTER TABLE origins ADD created_at TIMESTAMP not null default CURRENT_TIMESTAMP;
+UPDATE origins JOIN links ON links.domain = origins.domain AND links.id = origins.id
+SET origins.created_at = links.created_at;
+CREATE INDEX origins_created_at ON origins (created_at);
+ALTER TABLE links ADD expires_at TIMESTAMP NULL;
+CREATE INDEX links_expires_at ON links (expires_at);
@@ -0,0 +1,3 @@
+ALTER TABLE strikes DROP COLUMN bans;
+ALTER TABLE strikes DROP COLUMN banned_until;
+ALTER TABLE strikes DROP COLUMN updated_at;
@@ -0,0 +1,3 @@
+ALTER TABLE strikes ADD updated_at TIMESTAMP not null default CURRENT_TIMESTAMP;
+ALTER TABLE strikes ADD banned_until TIMESTAMP NULL;
+ALTER TABLE strikes ADD bans SMALLINT UNSIGNED not null default 0;
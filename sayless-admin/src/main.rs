@@ -0,0 +1,414 @@
+use std::{error::Error, net::IpAddr};
+
+use chrono::{NaiveDateTime, Utc};
+use clap::{Args, Parser, Subcommand};
+use sayless::{
+    audit::{self, purge_expired_client_ips, AuditAction, AuditTarget},
+    database::connect_db,
+    json_schemas::{create_token_params::CreateTokenParams, token_permissions::TokenPermissions},
+    links::{management, purge_expired_origins},
+    maintenance::MaintenanceJob,
+    service_config::{self, ServiceConfig},
+    strikes::{self, StrikeStatus},
+    tokens::{self, TokenIdentity},
+    MIGRATOR,
+};
+use simple_logger::SimpleLogger;
+use sqlx::{migrate::Migrate, MySql, Pool};
+
+type AdminResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// Administration of a sayless instance. Reads the same `config.toml` and `DATABASE_URL` as the
+/// server.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Manage API tokens
+    #[command(subcommand)]
+    Tokens(TokensCommand),
+    /// Manage strikes of IP addresses
+    #[command(subcommand)]
+    Strikes(StrikesCommand),
+    /// Manage short links. A running server picks up changes once its cached links expire.
+    #[command(subcommand)]
+    Links(LinksCommand),
+    /// Remove recorded IPs of links older than the configured retention period
+    PurgeIps,
+    /// Run a maintenance job once, like its schedule does, e.g. `expired_links`
+    RunJob { job: MaintenanceJob },
+    /// Inspect database migrations
+    #[command(subcommand)]
+    Migrations(MigrationsCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum TokensCommand {
+    /// Create a token and print it
+    Create {
+        #[arg(long)]
+        admin: bool,
+        #[arg(long)]
+        create_link: bool,
+        #[arg(long)]
+        view_ips: bool,
+        /// Expiration date as `YYYY-MM-DD HH:MM:SS` in UTC, defaults to a year from now
+        #[arg(long, value_parser = parse_timestamp)]
+        expires_at: Option<NaiveDateTime>,
+    },
+    /// List tokens without revealing them
+    List {
+        /// Include expired tokens
+        #[arg(long)]
+        all: bool,
+    },
+    /// Expire a token right away
+    Revoke(RevokeArgs),
+}
+
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+struct RevokeArgs {
+    /// The token to revoke
+    token: Option<String>,
+    /// Id of the token to revoke, as shown by `tokens list`
+    #[arg(long)]
+    id: Option<u64>,
+}
+
+#[derive(Debug, Subcommand)]
+enum StrikesCommand {
+    /// Show strikes recorded for an IP and whether it's banned
+    Show { ip: IpAddr },
+    /// Add strikes to an IP
+    Add {
+        ip: IpAddr,
+        #[arg(long, default_value_t = 1)]
+        amount: u16,
+    },
+    /// Remove all strikes of an IP and lift its ban
+    Clear { ip: IpAddr },
+}
+
+#[derive(Debug, Subcommand)]
+enum LinksCommand {
+    /// Stop a link from redirecting
+    Disable(LinkArgs),
+    /// Delete a link, its id is never reused
+    Delete(LinkArgs),
+    /// Stop a link from redirecting until it's released, its owner can't undo it
+    Quarantine(LinkArgs),
+    /// Lift the quarantine of a link
+    Release(LinkArgs),
+}
+
+#[derive(Debug, Args)]
+struct LinkArgs {
+    id: String,
+    /// Host of the short domain the link belongs to, required if domains are configured
+    #[arg(long)]
+    domain: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum MigrationsCommand {
+    /// List migrations and whether they are applied
+    Status,
+}
+
+/// Tokens listed per query
+const TOKEN_PAGE_SIZE: u32 = 500;
+
+fn parse_timestamp(value: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> AdminResult {
+    let cli = Cli::parse();
+
+    SimpleLogger::new()
+        .with_level(log::LevelFilter::Warn)
+        .env()
+        .init()?;
+
+    dotenvy::dotenv().ok();
+
+    let config = service_config::read_config().await?;
+    let db = connect_db().await?;
+
+    match cli.command {
+        Command::Tokens(command) => tokens_command(&db, command).await,
+        Command::Strikes(command) => strikes_command(&db, &config, command).await,
+        Command::Links(command) => links_command(&db, &config, command).await,
+        Command::PurgeIps => purge_ips(&db, &config).await,
+        Command::RunJob { job } => run_job(&db, &config, job).await,
+        Command::Migrations(MigrationsCommand::Status) => migration_status(&db).await,
+    }
+}
+
+async fn tokens_command(db: &Pool<MySql>, command: TokensCommand) -> AdminResult {
+    match command {
+        TokensCommand::Create {
+            admin,
+            create_link,
+            view_ips,
+            expires_at,
+        } => {
+            let (id, token) = tokens::insert_token(
+                db,
+                CreateTokenParams {
+                    perms: TokenPermissions {
+                        admin_perm: admin,
+                        create_link_perm: create_link,
+                        view_ips_perm: view_ips,
+                    },
+                    expires_at: expires_at.map(|date| date.and_utc()),
+                },
+            )
+            .await
+            .map_err(|status| format!("Creating token failed: {status}"))?;
+            record_event(db, AuditAction::TokenCreated, AuditTarget::Token(id)).await;
+            println!("{token}");
+        }
+        TokensCommand::List { all } => {
+            let now = Utc::now();
+            println!(
+                "{:>6}  {:<19}  {:<19}  permissions",
+                "id", "created", "expires"
+            );
+            let mut cursor = None;
+            loop {
+                let page = tokens::list_tokens(db, all, cursor, TOKEN_PAGE_SIZE)
+                    .await
+                    .map_err(|status| format!("Listing tokens failed: {status}"))?;
+                let done = page.len() < TOKEN_PAGE_SIZE as usize;
+                cursor = page.last().map(|last| last.id);
+                for token in page {
+                    let perms = [
+                        (token.admin_perm, "admin"),
+                        (token.create_link_perm, "create_link"),
+                        (token.view_ips_perm, "view_ips"),
+                    ]
+                    .into_iter()
+                    .filter_map(|(set, name)| set.then_some(name))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                    println!(
+                        "{:>6}  {}  {}  {}{}",
+                        token.id,
+                        token.created_at.format("%Y-%m-%d %H:%M:%S"),
+                        token.expires_at.format("%Y-%m-%d %H:%M:%S"),
+                        if perms.is_empty() { "-" } else { &perms },
+                        if token.expires_at <= now {
+                            " (expired)"
+                        } else {
+                            ""
+                        }
+                    );
+                }
+                if done {
+                    break;
+                }
+            }
+        }
+        TokensCommand::Revoke(RevokeArgs { token, id }) => {
+            let id = match (token, id) {
+                (_, Some(id)) => {
+                    let found = tokens::expire_token_by_id(db, id)
+                        .await
+                        .map_err(|status| format!("Revoking token failed: {status}"))?;
+                    if !found {
+                        return Err(format!("No token with id {id}").into());
+                    }
+                    id
+                }
+                (Some(token), None) => tokens::expire_token(db, &token)
+                    .await
+                    .map_err(|status| format!("Revoking token failed: {status}"))?
+                    .ok_or("No such token")?,
+                (None, None) => unreachable!("clap requires the token or its id"),
+            };
+            record_event(db, AuditAction::TokenRevoked, AuditTarget::Token(id)).await;
+            println!("Token revoked");
+        }
+    }
+    Ok(())
+}
+
+/// Amount of strikes, and the ban if there is one
+fn format_strikes(ip: IpAddr, status: StrikeStatus) -> String {
+    match (status.banned, status.banned_until) {
+        (false, _) => format!("{ip}: {}", status.amount),
+        (true, Some(until)) => format!("{ip}: {}, banned until {until}", status.amount),
+        (true, None) => format!("{ip}: {}, banned", status.amount),
+    }
+}
+
+async fn strikes_command(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    command: StrikesCommand,
+) -> AdminResult {
+    match command {
+        StrikesCommand::Show { ip } => {
+            let status = strikes::get_strikes(db, config, ip)
+                .await
+                .map_err(|status| format!("Looking up strikes failed: {status}"))?;
+            println!("{}", format_strikes(ip, status));
+        }
+        StrikesCommand::Add { ip, amount } => {
+            let status = strikes::add_strikes(db, config, ip, amount)
+                .await
+                .map_err(|status| format!("Adding strikes failed: {status}"))?;
+            record_event(db, AuditAction::StrikesAdded, AuditTarget::Ip(ip)).await;
+            println!("{}", format_strikes(ip, status));
+        }
+        StrikesCommand::Clear { ip } => {
+            strikes::set_strikes(db, config, ip, 0)
+                .await
+                .map_err(|status| format!("Clearing strikes failed: {status}"))?;
+            record_event(db, AuditAction::StrikesSet, AuditTarget::Ip(ip)).await;
+            println!("{ip}: 0");
+        }
+    }
+    Ok(())
+}
+
+async fn links_command(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    command: LinksCommand,
+) -> AdminResult {
+    let (LinksCommand::Disable(args)
+    | LinksCommand::Delete(args)
+    | LinksCommand::Quarantine(args)
+    | LinksCommand::Release(args)) = &command;
+    let domain = config
+        .domain(args.domain.as_deref())
+        .ok_or_else(|| match &args.domain {
+            Some(host) => format!("Domain `{host}` is not configured"),
+            None => "--domain is required when domains are configured".to_owned(),
+        })?;
+    let namespace = domain.namespace();
+
+    match &command {
+        LinksCommand::Disable(LinkArgs { id, .. }) => {
+            management::disable_link(db, namespace, id, TokenIdentity::Master)
+                .await
+                .map_err(|status| format!("Disabling link `{id}` failed: {status}"))?;
+            record_event(
+                db,
+                AuditAction::LinkDisabled,
+                AuditTarget::Link { namespace, id },
+            )
+            .await;
+            println!("Link `{id}` disabled");
+        }
+        LinksCommand::Delete(LinkArgs { id, .. }) => {
+            management::delete_link(db, namespace, id, TokenIdentity::Master)
+                .await
+                .map_err(|status| format!("Deleting link `{id}` failed: {status}"))?;
+            record_event(
+                db,
+                AuditAction::LinkDeleted,
+                AuditTarget::Link { namespace, id },
+            )
+            .await;
+            println!("Link `{id}` deleted");
+        }
+        LinksCommand::Quarantine(LinkArgs { id, .. }) => {
+            management::quarantine_link(db, namespace, id, TokenIdentity::Master)
+                .await
+                .map_err(|status| format!("Quarantining link `{id}` failed: {status}"))?;
+            record_event(
+                db,
+                AuditAction::LinkQuarantined,
+                AuditTarget::Link { namespace, id },
+            )
+            .await;
+            println!("Link `{id}` quarantined");
+        }
+        LinksCommand::Release(LinkArgs { id, .. }) => {
+            management::release_link(db, namespace, id, TokenIdentity::Master)
+                .await
+                .map_err(|status| format!("Releasing link `{id}` failed: {status}"))?;
+            record_event(
+                db,
+                AuditAction::LinkReleased,
+                AuditTarget::Link { namespace, id },
+            )
+            .await;
+            println!("Link `{id}` released");
+        }
+    }
+    Ok(())
+}
+
+async fn purge_ips(db: &Pool<MySql>, config: &ServiceConfig) -> AdminResult {
+    let ip_recording = config
+        .ip_recording
+        .as_ref()
+        .ok_or("IP recording is not enabled in the config")?;
+    let purged = purge_expired_origins(db, ip_recording.retention_period).await?;
+    println!("Removed {purged} recorded IPs");
+    let cleared = purge_expired_client_ips(db, ip_recording.retention_period).await?;
+    println!("Removed client IPs of {cleared} audit events");
+    Ok(())
+}
+
+async fn run_job(db: &Pool<MySql>, config: &ServiceConfig, job: MaintenanceJob) -> AdminResult {
+    if !job.is_available(config) {
+        return Err(format!("Maintenance job `{job}` is not available with the config").into());
+    }
+    let affected = job.execute(db, config).await?;
+    record_event(
+        db,
+        AuditAction::MaintenanceJobRun,
+        AuditTarget::Job(job.as_str()),
+    )
+    .await;
+    println!("Maintenance job `{job}` affected {affected} rows");
+    Ok(())
+}
+
+/// Actions taken with this tool are recorded like those of the master token
+async fn record_event(db: &Pool<MySql>, action: AuditAction, target: AuditTarget<'_>) {
+    audit::record_event(db, None, action, target, None).await;
+}
+
+async fn migration_status(db: &Pool<MySql>) -> AdminResult {
+    let mut conn = db.acquire().await?;
+    let applied = conn.list_applied_migrations().await?;
+
+    for migration in MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+    {
+        let status = match applied
+            .iter()
+            .find(|applied| applied.version == migration.version)
+        {
+            Some(applied) if applied.checksum != migration.checksum => "checksum mismatch",
+            Some(_) => "applied",
+            None => "pending",
+        };
+        println!(
+            "{:<14}  {:<17}  {}",
+            migration.version, status, migration.description
+        );
+    }
+    for applied in applied.iter().filter(|applied| {
+        !MIGRATOR
+            .iter()
+            .any(|migration| migration.version == applied.version)
+    }) {
+        println!("{:<14}  {:<17}", applied.version, "unknown");
+    }
+    Ok(())
+}
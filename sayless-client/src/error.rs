@@ -0,0 +1,44 @@
+use std::{error::Error, fmt};
+
+/// Error of a request made by [`crate::SaylessClient`]
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request couldn't be sent or the response couldn't be read
+    Http(reqwest::Error),
+    /// The server responded with an error status
+    Api { status: u16, error: String },
+}
+
+impl ClientError {
+    /// HTTP status the server responded with, if it responded
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Http(err) => err.status().map(|status| status.as_u16()),
+            Self::Api { status, .. } => Some(*status),
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(err) => write!(f, "Request failed: {err}"),
+            Self::Api { status, error } => write!(f, "Server responded with {status}: {error}"),
+        }
+    }
+}
+
+impl Error for ClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Http(err) => Some(err),
+            Self::Api { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Http(err)
+    }
+}
@@ -0,0 +1,157 @@
+use reqwest::{Method, RequestBuilder, Response};
+use sayless::responses::api_error::ApiErrorBody;
+use serde::{de::DeserializeOwned, Serialize};
+
+pub use sayless::{
+    json_schemas::{
+        create_link_params::CreateLinkParams, create_link_request::CreateLinkRequest,
+        create_links_request::CreateLinksRequest, create_token_params::CreateTokenParams,
+        redirect_code::RedirectCode, revoke_token_request::RevokeTokenRequest,
+        token_permissions::TokenPermissions,
+    },
+    responses::{
+        bulk_created_links::{BulkLinkOutcome, BulkLinkResult},
+        created_link::CreatedLink,
+        link_info::LinkInfo,
+        token_created::TokenCreated,
+    },
+    routes::config_info::ConfigInfo,
+};
+
+pub use self::error::ClientError;
+
+pub mod error;
+
+/// Async client of the versioned sayless API (`/api/v1`)
+#[derive(Debug, Clone)]
+pub struct SaylessClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl SaylessClient {
+    /// Client of the instance at `base_url`, e.g. `https://example.com`. Links are created on
+    /// the short domain of the URL.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        base_url.truncate(base_url.trim_end_matches('/').len());
+        Self {
+            http,
+            base_url,
+            token: None,
+        }
+    }
+
+    /// Sends the token with every request
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}/api/v1{path}", self.base_url));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send(request: RequestBuilder) -> Result<Response, ClientError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let error = match response.json::<ApiErrorBody>().await {
+            Ok(body) => body.error.into_owned(),
+            Err(_) => status
+                .canonical_reason()
+                .unwrap_or("Unknown error")
+                .to_owned(),
+        };
+        Err(ClientError::Api {
+            status: status.as_u16(),
+            error,
+        })
+    }
+
+    async fn send_json<T: DeserializeOwned>(
+        request: RequestBuilder,
+        body: &impl Serialize,
+    ) -> Result<T, ClientError> {
+        Ok(Self::send(request.json(body)).await?.json().await?)
+    }
+
+    /// Shortens a link
+    pub async fn create_link(
+        &self,
+        request: &CreateLinkRequest,
+    ) -> Result<CreatedLink, ClientError> {
+        Self::send_json(self.request(Method::POST, "/links"), request).await
+    }
+
+    /// Shortens multiple links at once. Invalid links are reported in the results.
+    pub async fn create_links(
+        &self,
+        request: &CreateLinksRequest,
+    ) -> Result<Vec<BulkLinkResult>, ClientError> {
+        Self::send_json(self.request(Method::POST, "/links/bulk"), request).await
+    }
+
+    /// Information about a link
+    pub async fn link_info(&self, id: &str) -> Result<LinkInfo, ClientError> {
+        let path = format!("/links/{}", encode_segment(id));
+        Ok(Self::send(self.request(Method::GET, &path))
+            .await?
+            .json()
+            .await?)
+    }
+
+    /// Creates a token and returns it. Requires admin permission.
+    pub async fn create_token(&self, params: &CreateTokenParams) -> Result<String, ClientError> {
+        let TokenCreated { token } =
+            Self::send_json(self.request(Method::POST, "/tokens"), params).await?;
+        Ok(token)
+    }
+
+    /// Revokes a token. The client's own token can always be revoked, other tokens require admin
+    /// permission.
+    pub async fn revoke_token(&self, token: &str) -> Result<(), ClientError> {
+        Self::send(
+            self.request(Method::POST, "/tokens/revoke")
+                .json(&RevokeTokenRequest {
+                    token: token.to_owned(),
+                }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Configuration of the instance
+    pub async fn config_info(&self) -> Result<ConfigInfo, ClientError> {
+        Ok(Self::send(self.request(Method::GET, "/config"))
+            .await?
+            .json()
+            .await?)
+    }
+}
+
+/// Percent-encodes characters that can't appear in a path segment as they are
+fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
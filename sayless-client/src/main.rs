@@ -0,0 +1,185 @@
+use std::error::Error;
+
+use chrono::NaiveDateTime;
+use clap::{Parser, Subcommand};
+use sayless_client::{
+    BulkLinkOutcome, CreateLinkParams, CreateLinkRequest, CreateLinksRequest, CreateTokenParams,
+    RedirectCode, SaylessClient, TokenPermissions,
+};
+
+/// Command-line client of the sayless API
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    /// Base URL of the instance, e.g. `https://example.com`
+    #[arg(long, env = "SAYLESS_URL")]
+    url: String,
+    /// Token sent with requests
+    #[arg(long, env = "SAYLESS_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Shorten a link and print its location
+    Create {
+        url: String,
+        #[command(flatten)]
+        options: LinkOptions,
+    },
+    /// Shorten multiple links and print the results
+    Bulk {
+        #[arg(required = true)]
+        urls: Vec<String>,
+        #[command(flatten)]
+        options: LinkOptions,
+    },
+    /// Print information about a link as JSON
+    Info { id: String },
+    /// Manage tokens
+    #[command(subcommand)]
+    Token(TokenCommand),
+    /// Print configuration of the instance as JSON
+    Config,
+}
+
+#[derive(Debug, clap::Args)]
+struct LinkOptions {
+    /// Protect the link with a password
+    #[arg(long)]
+    password: Option<String>,
+    /// Allow changing the destination later
+    #[arg(long)]
+    editable: bool,
+    /// Redirect with this status code instead of the configured default
+    #[arg(long, value_parser = parse_redirect_code)]
+    redirect_code: Option<RedirectCode>,
+    /// Append the query string of the short link to the destination
+    #[arg(long)]
+    forward_query: bool,
+    /// Append the path after the id to the destination
+    #[arg(long)]
+    forward_path: bool,
+    /// `max-age` of permanent redirects, in seconds
+    #[arg(long)]
+    cache_max_age: Option<u32>,
+    /// Stop redirecting at this time, as `YYYY-MM-DD HH:MM:SS` in UTC
+    #[arg(long, value_parser = parse_timestamp)]
+    expires_at: Option<NaiveDateTime>,
+}
+
+impl LinkOptions {
+    fn into_params(self) -> (Option<String>, CreateLinkParams) {
+        (
+            self.password,
+            CreateLinkParams {
+                editable: self.editable,
+                password_hash: None,
+                redirect_code: self.redirect_code,
+                forward_query: self.forward_query,
+                forward_path: self.forward_path,
+                cache_max_age: self.cache_max_age,
+                expires_at: self.expires_at.map(|date| date.and_utc()),
+            },
+        )
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum TokenCommand {
+    /// Create a token and print it
+    Create {
+        #[arg(long)]
+        admin: bool,
+        #[arg(long)]
+        create_link: bool,
+        #[arg(long)]
+        view_ips: bool,
+        /// Expiration date as `YYYY-MM-DD HH:MM:SS` in UTC
+        #[arg(long, value_parser = parse_timestamp)]
+        expires_at: Option<NaiveDateTime>,
+    },
+    /// Revoke a token
+    Revoke { token: String },
+}
+
+fn parse_redirect_code(value: &str) -> Result<RedirectCode, String> {
+    value.parse::<u16>().map_err(|e| e.to_string())?.try_into()
+}
+
+fn parse_timestamp(value: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let cli = Cli::parse();
+
+    let mut client = SaylessClient::new(cli.url);
+    if let Some(token) = cli.token {
+        client = client.with_token(token);
+    }
+
+    match cli.command {
+        Command::Create { url, options } => {
+            let (password, params) = options.into_params();
+            let created = client
+                .create_link(&CreateLinkRequest {
+                    url,
+                    password,
+                    params,
+                })
+                .await?;
+            println!("{}", created.location);
+        }
+        Command::Bulk { urls, options } => {
+            let (password, params) = options.into_params();
+            let results = client
+                .create_links(&CreateLinksRequest {
+                    urls,
+                    password,
+                    params,
+                })
+                .await?;
+            for result in results {
+                match result.outcome {
+                    BulkLinkOutcome::Id(id) => println!("{id}\t{}", result.link),
+                    BulkLinkOutcome::Error(error) => eprintln!("{error}\t{}", result.link),
+                }
+            }
+        }
+        Command::Info { id } => {
+            let info = client.link_info(&id).await?;
+            println!("{}", serde_json::to_string_pretty(&info)?);
+        }
+        Command::Token(TokenCommand::Create {
+            admin,
+            create_link,
+            view_ips,
+            expires_at,
+        }) => {
+            let token = client
+                .create_token(&CreateTokenParams {
+                    perms: TokenPermissions {
+                        admin_perm: admin,
+                        create_link_perm: create_link,
+                        view_ips_perm: view_ips,
+                    },
+                    expires_at: expires_at.map(|date| date.and_utc()),
+                })
+                .await?;
+            println!("{token}");
+        }
+        Command::Token(TokenCommand::Revoke { token }) => {
+            client.revoke_token(&token).await?;
+            println!("Token revoked");
+        }
+        Command::Config => {
+            let config = client.config_info().await?;
+            println!("{}", serde_json::to_string_pretty(&config)?);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,427 @@
+use std::{future::IntoFuture, net::SocketAddr, sync::Arc, time::Duration};
+
+use sayless::{
+    links::cache::LinkCache,
+    routes::create_router,
+    service_config::{reload::SharedConfig, token::MasterTokens, ServiceConfig},
+    ServiceState, MIGRATOR,
+};
+use sayless_client::{
+    BulkLinkOutcome, ClientError, CreateLinkParams, CreateLinkRequest, CreateLinksRequest,
+    CreateTokenParams, SaylessClient, TokenPermissions,
+};
+use sqlx::mysql::MySqlPoolOptions;
+
+const MASTER_TOKEN: &str = "test-master-token";
+
+const CONFIG: &str = r#"
+max_bulk_links = 3
+
+[redirect_cache]
+ttl = "90s"
+
+[token_config]
+creation_requires_auth = true
+"#;
+
+/// Database URL of the tests that need one. Those tests are ignored unless run with
+/// `--ignored`, and fail if it isn't set.
+fn test_database_url() -> String {
+    std::env::var("SAYLESS_TEST_DATABASE_URL")
+        .expect("SAYLESS_TEST_DATABASE_URL must be set for tests that need a database")
+}
+
+/// Serves the real router on an ephemeral port and returns its base URL. Without a database URL
+/// the pool points at a closed port, so only requests answered before any query succeed.
+async fn serve(database_url: Option<&str>) -> String {
+    // Config info reports the log level, which requires a logger. Only the first test sets it.
+    simple_logger::init_with_level(log::Level::Warn).ok();
+
+    let mut config: ServiceConfig = toml::from_str(CONFIG).unwrap();
+    if let Some(token_config) = &mut config.token_config {
+        token_config.master_tokens = MasterTokens::parse(MASTER_TOKEN).unwrap();
+    }
+
+    let db = MySqlPoolOptions::new()
+        .acquire_timeout(Duration::from_secs(1))
+        .connect_lazy(database_url.unwrap_or("mysql://sayless@127.0.0.1:9/sayless"))
+        .unwrap();
+    if database_url.is_some() {
+        MIGRATOR.run(&db).await.unwrap();
+    }
+
+    let router = create_router(&config).with_state(ServiceState {
+        db: Arc::new(db),
+        link_cache: Arc::new(LinkCache::new(&config.redirect_cache)),
+        config: SharedConfig::new(config),
+        password_attempts: Arc::default(),
+        maintenance: Arc::default(),
+    });
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .into_future(),
+    );
+
+    format!("http://{addr}")
+}
+
+/// Client of a server started by `serve`
+async fn spawn_server(database_url: Option<&str>) -> SaylessClient {
+    SaylessClient::new(serve(database_url).await)
+}
+
+/// Status of a versioned API request the client has no method for
+async fn api_status(
+    base_url: &str,
+    method: reqwest::Method,
+    path: &str,
+    token: &str,
+    body: Option<serde_json::Value>,
+) -> u16 {
+    let request = reqwest::Client::new()
+        .request(method, format!("{base_url}/api/v1{path}"))
+        .bearer_auth(token);
+    let request = match body {
+        Some(body) => request.json(&body),
+        None => request,
+    };
+    request.send().await.unwrap().status().as_u16()
+}
+
+fn link_request(url: &str) -> CreateLinkRequest {
+    CreateLinkRequest {
+        url: url.to_owned(),
+        password: None,
+        params: CreateLinkParams::default(),
+    }
+}
+
+fn links_request(urls: &[&str]) -> CreateLinksRequest {
+    CreateLinksRequest {
+        urls: urls.iter().map(|url| url.to_string()).collect(),
+        password: None,
+        params: CreateLinkParams::default(),
+    }
+}
+
+/// Creates a token that can only create links, expiring in an hour
+async fn create_link_token(master: &SaylessClient) -> String {
+    master
+        .create_token(&CreateTokenParams {
+            perms: TokenPermissions::new().create_link(),
+            expires_at: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+        })
+        .await
+        .unwrap()
+}
+
+fn assert_status<T: std::fmt::Debug>(result: Result<T, ClientError>, expected: u16) {
+    match result {
+        Err(ClientError::Api { status, .. }) => assert_eq!(status, expected),
+        other => panic!("Expected error status {expected}, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn config_info() {
+    let client = spawn_server(None).await;
+
+    let info = client.config_info().await.unwrap();
+    assert_eq!(info.service_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.max_bulk_links, 3);
+    assert!(info.ip_recording.is_none());
+    assert!(info.tokens.unwrap().link_creation_requires_auth);
+    assert!(info.domains.is_empty());
+    assert_eq!(info.redirect_cache.ttl, chrono::Duration::seconds(90));
+    assert_eq!(
+        info.link_passwords.lockout_period,
+        chrono::Duration::minutes(15)
+    );
+    assert_eq!(info.reload_count, 0);
+    assert!(info.last_reload.is_none());
+}
+
+#[tokio::test]
+async fn create_link_requires_token() {
+    let client = spawn_server(None).await;
+
+    assert_status(
+        client
+            .create_link(&link_request("https://example.com"))
+            .await,
+        401,
+    );
+    assert_status(
+        client
+            .create_links(&links_request(&["https://example.com"]))
+            .await,
+        401,
+    );
+}
+
+#[tokio::test]
+async fn create_link_rejects_invalid_link() {
+    let client = spawn_server(None).await.with_token(MASTER_TOKEN);
+
+    assert_status(client.create_link(&link_request("not a link")).await, 400);
+}
+
+#[tokio::test]
+async fn create_links_checks_amount() {
+    let client = spawn_server(None).await.with_token(MASTER_TOKEN);
+
+    assert_status(client.create_links(&links_request(&[])).await, 400);
+    assert_status(
+        client
+            .create_links(&links_request(&[
+                "https://example.com/1",
+                "https://example.com/2",
+                "https://example.com/3",
+                "https://example.com/4",
+            ]))
+            .await,
+        413,
+    );
+}
+
+#[tokio::test]
+async fn token_endpoints_require_token() {
+    let client = spawn_server(None).await;
+
+    assert_status(
+        client
+            .create_token(&CreateTokenParams {
+                perms: TokenPermissions::new().create_link(),
+                expires_at: None,
+            })
+            .await,
+        401,
+    );
+    assert_status(client.revoke_token("some-token").await, 401);
+}
+
+#[tokio::test]
+#[ignore = "needs SAYLESS_TEST_DATABASE_URL"]
+async fn link_and_token_lifecycle() {
+    let database_url = test_database_url();
+    let client = spawn_server(Some(&database_url)).await;
+    let master = client.clone().with_token(MASTER_TOKEN);
+
+    let token = create_link_token(&master).await;
+    let user = client.clone().with_token(&token);
+
+    let created = user
+        .create_link(&link_request("https://example.com/client-test"))
+        .await
+        .unwrap();
+    assert!(created.location.ends_with(&created.id));
+
+    let info = client.link_info(&created.id).await.unwrap();
+    assert_eq!(info.id, created.id);
+    assert_eq!(
+        info.link.as_deref(),
+        Some("https://example.com/client-test")
+    );
+
+    let results = user
+        .create_links(&links_request(&[
+            "https://example.com/client-test",
+            "not a link",
+        ]))
+        .await
+        .unwrap();
+    assert!(matches!(&results[0].outcome, BulkLinkOutcome::Id(id) if *id == created.id));
+    assert!(matches!(results[1].outcome, BulkLinkOutcome::Error(_)));
+
+    user.revoke_token(&token).await.unwrap();
+    assert_status(
+        user.create_link(&link_request("https://example.com/revoked"))
+            .await,
+        401,
+    );
+
+    assert_status(client.link_info("does-not-exist").await, 404);
+}
+
+#[tokio::test]
+#[ignore = "needs SAYLESS_TEST_DATABASE_URL"]
+async fn non_admin_tokens_cant_manage_tokens() {
+    let database_url = test_database_url();
+    let client = spawn_server(Some(&database_url)).await;
+    let master = client.clone().with_token(MASTER_TOKEN);
+    let token = create_link_token(&master).await;
+    let other = create_link_token(&master).await;
+    let user = client.clone().with_token(&token);
+
+    assert_status(
+        user.create_token(&CreateTokenParams {
+            perms: TokenPermissions::new().admin(),
+            expires_at: None,
+        })
+        .await,
+        403,
+    );
+    assert_status(user.revoke_token(&other).await, 403);
+
+    // The other token still works and can revoke itself
+    let other_user = client.clone().with_token(&other);
+    other_user
+        .create_link(&link_request("https://example.com/not-revoked"))
+        .await
+        .unwrap();
+    other_user.revoke_token(&other).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs SAYLESS_TEST_DATABASE_URL"]
+async fn non_admin_tokens_cant_list_tokens_or_strikes() {
+    use reqwest::Method;
+
+    let database_url = test_database_url();
+    let base_url = serve(Some(&database_url)).await;
+    let master = SaylessClient::new(&base_url).with_token(MASTER_TOKEN);
+    let token = create_link_token(&master).await;
+
+    assert_eq!(
+        api_status(&base_url, Method::GET, "/tokens", &token, None).await,
+        403
+    );
+    assert_eq!(
+        api_status(&base_url, Method::POST, "/tokens/1/revoke", &token, None).await,
+        403
+    );
+    assert_eq!(
+        api_status(&base_url, Method::GET, "/strikes", &token, None).await,
+        403
+    );
+    assert_eq!(
+        api_status(&base_url, Method::GET, "/strikes/192.0.2.1", &token, None).await,
+        403
+    );
+    assert_eq!(
+        api_status(&base_url, Method::GET, "/tokens", MASTER_TOKEN, None).await,
+        200
+    );
+    assert_eq!(
+        api_status(&base_url, Method::GET, "/strikes", MASTER_TOKEN, None).await,
+        200
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs SAYLESS_TEST_DATABASE_URL"]
+async fn non_admin_tokens_cant_run_maintenance_jobs() {
+    use reqwest::Method;
+
+    let database_url = test_database_url();
+    let base_url = serve(Some(&database_url)).await;
+    let master = SaylessClient::new(&base_url).with_token(MASTER_TOKEN);
+    let token = create_link_token(&master).await;
+
+    assert_eq!(
+        api_status(&base_url, Method::GET, "/maintenance", &token, None).await,
+        403
+    );
+    assert_eq!(
+        api_status(
+            &base_url,
+            Method::POST,
+            "/maintenance/strike_decay/run",
+            &token,
+            None
+        )
+        .await,
+        403
+    );
+    assert_eq!(
+        api_status(&base_url, Method::GET, "/maintenance", MASTER_TOKEN, None).await,
+        200
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs SAYLESS_TEST_DATABASE_URL"]
+async fn non_admin_tokens_cant_clear_strikes() {
+    use reqwest::Method;
+
+    let database_url = test_database_url();
+    let base_url = serve(Some(&database_url)).await;
+    let master = SaylessClient::new(&base_url).with_token(MASTER_TOKEN);
+    let token = create_link_token(&master).await;
+    let path = "/strikes/192.0.2.50";
+    let amount = |amount: u16| Some(serde_json::json!({ "amount": amount }));
+
+    assert_eq!(
+        api_status(&base_url, Method::PUT, path, MASTER_TOKEN, amount(30)).await,
+        200
+    );
+    assert_eq!(
+        api_status(&base_url, Method::PUT, path, &token, amount(0)).await,
+        403
+    );
+
+    let strikes: serde_json::Value = reqwest::Client::new()
+        .get(format!("{base_url}/api/v1{path}"))
+        .bearer_auth(MASTER_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(strikes["amount"], 30);
+    assert_eq!(strikes["banned"], true);
+
+    assert_eq!(
+        api_status(&base_url, Method::PUT, path, MASTER_TOKEN, amount(0)).await,
+        200
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs SAYLESS_TEST_DATABASE_URL"]
+async fn quarantined_link_destination_is_hidden() {
+    use reqwest::Method;
+
+    let database_url = test_database_url();
+    let base_url = serve(Some(&database_url)).await;
+    let client = SaylessClient::new(&base_url);
+    let master = client.clone().with_token(MASTER_TOKEN);
+    let token = create_link_token(&master).await;
+    let owner = client.clone().with_token(&token);
+
+    let created = owner
+        .create_link(&link_request("https://example.com/quarantine-test"))
+        .await
+        .unwrap();
+    let quarantine = format!("/links/{}/quarantine", created.id);
+    assert_eq!(
+        api_status(&base_url, Method::POST, &quarantine, MASTER_TOKEN, None).await,
+        204
+    );
+
+    for info in [
+        client.link_info(&created.id).await.unwrap(),
+        owner.link_info(&created.id).await.unwrap(),
+    ] {
+        assert!(info.quarantined_at.is_some());
+        assert!(info.link.is_none());
+        assert!(info.hash.is_none());
+    }
+    assert_eq!(
+        master.link_info(&created.id).await.unwrap().link.as_deref(),
+        Some("https://example.com/quarantine-test")
+    );
+
+    let release = format!("/links/{}/release", created.id);
+    assert_eq!(
+        api_status(&base_url, Method::POST, &release, MASTER_TOKEN, None).await,
+        204
+    );
+}
@@ -0,0 +1,309 @@
+use dioxus::prelude::*;
+
+use crate::{
+    api::{self, ApiError, LinkAction, TokenPermissions},
+    session::Session,
+};
+
+/// Management of tokens, strikes and links. Every action is checked by the server against the
+/// permissions of the session token.
+pub fn Admin(cx: Scope) -> Element {
+    let session = use_shared_state::<Session>(cx)?;
+    let Some(token) = session.read().token.clone() else {
+        return render! { p { "Enter a token to use the admin section." } };
+    };
+
+    render! {
+        section {
+            h2 { "Tokens" }
+            Tokens { token: token.clone() }
+        }
+        section {
+            h2 { "Strikes" }
+            Strikes { token: token.clone() }
+        }
+        section {
+            h2 { "Link moderation" }
+            LinkModeration { token: token }
+        }
+        section {
+            h2 { "Configuration" }
+            ConfigView {}
+        }
+    }
+}
+
+/// Outcome of the last action of a section
+fn status_view(status: &Option<Result<String, ApiError>>) -> Option<LazyNodes<'_, '_>> {
+    match status {
+        Some(Ok(message)) => Some(rsx! { p { class: "success", "{message}" } }),
+        Some(Err(err)) => Some(rsx! { p { class: "error", "{err}" } }),
+        None => None,
+    }
+}
+
+#[derive(Props, PartialEq)]
+struct TokenProps {
+    token: String,
+}
+
+fn Tokens(cx: Scope<TokenProps>) -> Element {
+    let include_expired = use_state(cx, || false);
+    let reload = use_state(cx, || 0u32);
+    let tokens = use_future(
+        cx,
+        (&cx.props.token, include_expired.get(), reload.get()),
+        |(token, all, _)| async move { api::list_tokens(&token, all).await },
+    );
+    let status = use_state(cx, || None::<Result<String, ApiError>>);
+
+    let perms = use_state(cx, TokenPermissions::default);
+    let expires_at = use_state(cx, String::new);
+
+    let create = move |_| {
+        let token = cx.props.token.clone();
+        let perms = *perms.get();
+        let expires_at = expires_at.get().trim().to_owned();
+        to_owned![status, reload];
+        cx.spawn(async move {
+            let expires_at = (!expires_at.is_empty()).then_some(expires_at.as_str());
+            status.set(Some(
+                api::create_token(&token, perms, expires_at)
+                    .await
+                    .map(|created| format!("Created token, it won't be shown again: {created}")),
+            ));
+            reload.modify(|count| count + 1);
+        });
+    };
+
+    let list = match tokens.value() {
+        Some(Ok(tokens)) => rsx! {
+            table {
+                tr {
+                    th { "Id" }
+                    th { "Created" }
+                    th { "Expires" }
+                    th { "Permissions" }
+                    th {}
+                }
+                for entry in tokens {
+                    tr { key: "{entry.id}",
+                        td { "{entry.id}" }
+                        td { "{entry.created_at}" }
+                        td { "{entry.expires_at}" }
+                        td { "{entry.perms.names()}" }
+                        td {
+                            button {
+                                onclick: {
+                                    let id = entry.id;
+                                    move |_| {
+                                        let token = cx.props.token.clone();
+                                        to_owned![status, reload];
+                                        cx.spawn(async move {
+                                            status.set(Some(
+                                                api::revoke_token(&token, id)
+                                                    .await
+                                                    .map(|()| format!("Revoked token {id}")),
+                                            ));
+                                            reload.modify(|count| count + 1);
+                                        });
+                                    }
+                                },
+                                "Revoke"
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        Some(Err(err)) => rsx! { p { class: "error", "{err}" } },
+        None => rsx! { p { "Loading…" } },
+    };
+
+    render! {
+        label {
+            input {
+                r#type: "checkbox",
+                checked: *include_expired.get(),
+                oninput: move |evt| include_expired.set(evt.value == "true")
+            }
+            "Include expired"
+        }
+        list
+        form { prevent_default: "onsubmit", onsubmit: create,
+            PermissionCheckbox {
+                label: "admin",
+                checked: perms.admin_perm,
+                onchange: move |set| {
+                    perms.modify(|perms| TokenPermissions { admin_perm: set, ..*perms })
+                }
+            }
+            PermissionCheckbox {
+                label: "create_link",
+                checked: perms.create_link_perm,
+                onchange: move |set| {
+                    perms.modify(|perms| TokenPermissions { create_link_perm: set, ..*perms })
+                }
+            }
+            PermissionCheckbox {
+                label: "view_ips",
+                checked: perms.view_ips_perm,
+                onchange: move |set| {
+                    perms.modify(|perms| TokenPermissions { view_ips_perm: set, ..*perms })
+                }
+            }
+            input {
+                placeholder: "Expires (YYYY-MM-DD HH:MM:SS UTC)",
+                value: "{expires_at}",
+                oninput: move |evt| expires_at.set(evt.value.clone())
+            }
+            button { r#type: "submit", "Create token" }
+        }
+        status_view(status.get())
+    }
+}
+
+#[derive(Props)]
+struct PermissionCheckboxProps<'a> {
+    label: &'static str,
+    checked: bool,
+    onchange: EventHandler<'a, bool>,
+}
+
+fn PermissionCheckbox<'a>(cx: Scope<'a, PermissionCheckboxProps<'a>>) -> Element<'a> {
+    render! {
+        label {
+            input {
+                r#type: "checkbox",
+                checked: cx.props.checked,
+                oninput: move |evt| cx.props.onchange.call(evt.value == "true")
+            }
+            cx.props.label
+        }
+    }
+}
+
+fn Strikes(cx: Scope<TokenProps>) -> Element {
+    let search = use_state(cx, String::new);
+    let query = use_state(cx, String::new);
+    let reload = use_state(cx, || 0u32);
+    let strikes = use_future(
+        cx,
+        (&cx.props.token, query.get(), reload.get()),
+        |(token, query, _)| async move { api::list_strikes(&token, &query).await },
+    );
+    let status = use_state(cx, || None::<Result<String, ApiError>>);
+
+    let list = match strikes.value() {
+        Some(Ok(strikes)) if strikes.is_empty() => rsx! { p { "No strikes recorded." } },
+        Some(Ok(strikes)) => rsx! {
+            table {
+                tr {
+                    th { "IP" }
+                    th { "Strikes" }
+                    th { "Banned" }
+                    th {}
+                }
+                for entry in strikes {
+                    tr { key: "{entry.ip}",
+                        td { "{entry.ip}" }
+                        td { "{entry.amount}" }
+                        td { "{entry.ban()}" }
+                        td {
+                            button {
+                                onclick: {
+                                    let ip = entry.ip.clone();
+                                    move |_| {
+                                        let token = cx.props.token.clone();
+                                        let ip = ip.clone();
+                                        to_owned![status, reload];
+                                        cx.spawn(async move {
+                                            status.set(Some(
+                                                api::clear_strikes(&token, &ip)
+                                                    .await
+                                                    .map(|()| format!("Cleared strikes of {ip}")),
+                                            ));
+                                            reload.modify(|count| count + 1);
+                                        });
+                                    }
+                                },
+                                "Clear"
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        Some(Err(err)) => rsx! { p { class: "error", "{err}" } },
+        None => rsx! { p { "Loading…" } },
+    };
+
+    render! {
+        form {
+            prevent_default: "onsubmit",
+            onsubmit: move |_| query.set(search.get().trim().to_owned()),
+            input {
+                placeholder: "Search by IP",
+                value: "{search}",
+                oninput: move |evt| search.set(evt.value.clone())
+            }
+            button { r#type: "submit", "Search" }
+        }
+        list
+        status_view(status.get())
+    }
+}
+
+fn LinkModeration(cx: Scope<TokenProps>) -> Element {
+    let id = use_state(cx, String::new);
+    let status = use_state(cx, || None::<Result<String, ApiError>>);
+
+    render! {
+        form { prevent_default: "onsubmit",
+            input {
+                placeholder: "Link id",
+                value: "{id}",
+                oninput: move |evt| id.set(evt.value.clone())
+            }
+            for action in LinkAction::ALL {
+                button {
+                    key: "{action.title()}",
+                    r#type: "button",
+                    disabled: id.get().trim().is_empty(),
+                    onclick: move |_| {
+                        let token = cx.props.token.clone();
+                        let id = id.get().trim().to_owned();
+                        to_owned![status];
+                        cx.spawn(async move {
+                            status.set(Some(
+                                api::moderate_link(&token, &id, action)
+                                    .await
+                                    .map(|()| format!("{}: done for `{id}`", action.title())),
+                            ));
+                        });
+                    },
+                    action.title()
+                }
+            }
+        }
+        p {
+            "Quarantined links stop redirecting until an admin releases them, their owners can't "
+            "undo it."
+        }
+        status_view(status.get())
+    }
+}
+
+/// Configuration info of the instance as reported by the server
+fn ConfigView(cx: Scope) -> Element {
+    let config = use_future(cx, (), |_| api::raw_config_info());
+
+    match config.value() {
+        Some(Ok(config)) => {
+            let config = serde_json::to_string_pretty(config).unwrap_or_default();
+            render! { pre { class: "config", "{config}" } }
+        }
+        Some(Err(err)) => render! { p { class: "error", "{err}" } },
+        None => render! { p { "Loading…" } },
+    }
+}
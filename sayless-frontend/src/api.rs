@@ -0,0 +1,434 @@
+use std::fmt;
+
+use reqwest::{header, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the API endpoints used by the frontend, always served regardless of `[routes]`
+const API_PREFIX: &str = "/l";
+
+/// Prefix of the versioned JSON API, used by the admin section
+const V1_PREFIX: &str = "/api/v1";
+
+/// Entries requested per page of the admin lists, the most the API returns
+const PAGE_SIZE: u32 = 500;
+
+/// Origin the frontend was loaded from, the API is served from the same one
+pub fn origin() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().origin().ok())
+        .unwrap_or_default()
+}
+
+/// Host name the frontend was loaded from, used to find the short domain config
+pub fn hostname() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().hostname().ok())
+        .unwrap_or_default()
+}
+
+/// Failed API request, displayed to the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError(pub String);
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        Self(format!("Request failed: {err}"))
+    }
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        Self(
+            match status {
+                StatusCode::BAD_REQUEST => "The link is invalid",
+                StatusCode::UNAUTHORIZED => "A valid token is required",
+                StatusCode::FORBIDDEN => "Not allowed with this token",
+                StatusCode::NOT_FOUND => "Link not found",
+                StatusCode::GONE => "The link was deleted or disabled",
+                StatusCode::TOO_MANY_REQUESTS => "Too many attempts, try again later",
+                _ => return Self(format!("Request failed with status {status}")),
+            }
+            .to_owned(),
+        )
+    }
+}
+
+fn request_at(
+    method: reqwest::Method,
+    prefix: &str,
+    path: &str,
+    token: Option<&str>,
+) -> RequestBuilder {
+    let request = reqwest::Client::new().request(method, format!("{}{prefix}{path}", origin()));
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+fn request(method: reqwest::Method, path: &str, token: Option<&str>) -> RequestBuilder {
+    request_at(method, API_PREFIX, path, token)
+}
+
+fn v1_request(method: reqwest::Method, path: &str, token: &str) -> RequestBuilder {
+    request_at(method, V1_PREFIX, path, Some(token))
+}
+
+async fn send(request: RequestBuilder) -> Result<reqwest::Response, ApiError> {
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(response.status().into());
+    }
+    Ok(response)
+}
+
+/// Makes a location returned by the API absolute, it's relative if no base URL is configured
+pub fn absolute_location(location: &str) -> String {
+    if location.starts_with('/') {
+        format!("{}{location}", origin())
+    } else {
+        location.to_owned()
+    }
+}
+
+/// Shortens the link and returns the location of the short link
+pub async fn create_link(
+    token: Option<&str>,
+    url: &str,
+    password: Option<&str>,
+) -> Result<String, ApiError> {
+    let mut request = request(reqwest::Method::POST, "/create", token).body(url.to_owned());
+    if let Some(password) = password {
+        request = request.header("X-Link-Password", password);
+    }
+    let response = send(request).await?;
+    let location = response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .ok_or_else(|| ApiError("Server didn't return the short link".to_owned()))?;
+    Ok(absolute_location(location))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LinkInfo {
+    pub id: String,
+    #[serde(default)]
+    pub link: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub editable: bool,
+    #[serde(default)]
+    pub password_protected: bool,
+    #[serde(default)]
+    pub disabled_at: Option<String>,
+    #[serde(default)]
+    pub quarantined_at: Option<String>,
+    #[serde(default)]
+    pub redirect_code: Option<u16>,
+    #[serde(default)]
+    pub forward_query: bool,
+    #[serde(default)]
+    pub forward_path: bool,
+    #[serde(default)]
+    pub revisions: Vec<LinkRevision>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LinkRevision {
+    pub id: u64,
+    pub previous_link: String,
+    pub link: String,
+    pub changed_at: String,
+}
+
+pub async fn link_info(token: Option<&str>, id: &str) -> Result<LinkInfo, ApiError> {
+    let path = format!("/{}/info", encode_segment(id));
+    Ok(send(request(reqwest::Method::GET, &path, token))
+        .await?
+        .json()
+        .await?)
+}
+
+/// Parts of the configuration the frontend depends on
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConfigInfo {
+    pub tokens: Option<TokenConfigInfo>,
+    #[serde(default)]
+    pub domains: Vec<DomainConfigInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TokenConfigInfo {
+    pub link_creation_requires_auth: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DomainConfigInfo {
+    pub host: String,
+    #[serde(default)]
+    pub link_creation_requires_auth: Option<bool>,
+}
+
+impl ConfigInfo {
+    /// Whether creating links on the host the frontend was loaded from requires a token
+    pub fn creation_requires_auth(&self, host: &str) -> bool {
+        let Some(tokens) = &self.tokens else {
+            return false;
+        };
+        self.domains
+            .iter()
+            .find(|domain| domain.host.eq_ignore_ascii_case(host))
+            .and_then(|domain| domain.link_creation_requires_auth)
+            .unwrap_or(tokens.link_creation_requires_auth)
+    }
+}
+
+pub async fn config_info() -> Result<ConfigInfo, ApiError> {
+    Ok(send(
+        request(reqwest::Method::GET, "/config_info", None)
+            .header(header::ACCEPT, "application/json"),
+    )
+    .await?
+    .json()
+    .await?)
+}
+
+/// Sends a request of the admin section, naming what wasn't found on 404
+async fn send_admin(request: RequestBuilder, subject: &str) -> Result<reqwest::Response, ApiError> {
+    send(request).await.map_err(|err| {
+        if err == StatusCode::NOT_FOUND.into() {
+            ApiError(format!("{subject} not found"))
+        } else {
+            err
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenPermissions {
+    #[serde(default)]
+    pub admin_perm: bool,
+    #[serde(default)]
+    pub create_link_perm: bool,
+    #[serde(default)]
+    pub view_ips_perm: bool,
+}
+
+impl TokenPermissions {
+    /// Comma-separated names of the granted permissions
+    pub fn names(&self) -> String {
+        [
+            (self.admin_perm, "admin"),
+            (self.create_link_perm, "create_link"),
+            (self.view_ips_perm, "view_ips"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TokenEntry {
+    pub id: u64,
+    pub created_at: String,
+    pub expires_at: String,
+    #[serde(flatten)]
+    pub perms: TokenPermissions,
+}
+
+#[derive(Deserialize)]
+struct TokenList {
+    tokens: Vec<TokenEntry>,
+    #[serde(default)]
+    next_cursor: Option<u64>,
+}
+
+/// All pages of the token list
+pub async fn list_tokens(token: &str, all: bool) -> Result<Vec<TokenEntry>, ApiError> {
+    let mut tokens = Vec::new();
+    let mut cursor = None;
+    loop {
+        let mut request = v1_request(reqwest::Method::GET, "/tokens", token)
+            .query(&[("all", all.to_string()), ("limit", PAGE_SIZE.to_string())]);
+        if let Some(cursor) = cursor {
+            request = request.query(&[("cursor", cursor)]);
+        }
+        let list: TokenList = send(request).await?.json().await?;
+        tokens.extend(list.tokens);
+        match list.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(tokens),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CreateTokenParams<'a> {
+    #[serde(flatten)]
+    perms: TokenPermissions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct TokenCreated {
+    token: String,
+}
+
+/// Creates a token and returns it, it can't be retrieved again later.
+/// `expires_at` is `YYYY-MM-DD HH:MM:SS` in UTC.
+pub async fn create_token(
+    token: &str,
+    perms: TokenPermissions,
+    expires_at: Option<&str>,
+) -> Result<String, ApiError> {
+    let created: TokenCreated = send(
+        v1_request(reqwest::Method::POST, "/tokens", token)
+            .json(&CreateTokenParams { perms, expires_at }),
+    )
+    .await?
+    .json()
+    .await?;
+    Ok(created.token)
+}
+
+pub async fn revoke_token(token: &str, id: u64) -> Result<(), ApiError> {
+    let path = format!("/tokens/{id}/revoke");
+    send_admin(v1_request(reqwest::Method::POST, &path, token), "Token").await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Strikes {
+    pub ip: String,
+    pub amount: u16,
+    #[serde(default)]
+    pub banned: bool,
+    #[serde(default)]
+    pub banned_until: Option<String>,
+}
+
+impl Strikes {
+    /// Whether and until when the IP is banned
+    pub fn ban(&self) -> String {
+        match (self.banned, &self.banned_until) {
+            (false, _) => String::new(),
+            (true, Some(until)) => format!("Until {until}"),
+            (true, None) => "Yes".to_owned(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct StrikesList {
+    strikes: Vec<Strikes>,
+    #[serde(default)]
+    next_cursor: Option<String>,
+}
+
+/// All pages of the strikes list, most strikes first
+pub async fn list_strikes(token: &str, search: &str) -> Result<Vec<Strikes>, ApiError> {
+    let mut strikes = Vec::new();
+    let mut cursor = None;
+    loop {
+        let mut request = v1_request(reqwest::Method::GET, "/strikes", token)
+            .query(&[("search", search), ("limit", &PAGE_SIZE.to_string())]);
+        if let Some(cursor) = &cursor {
+            request = request.query(&[("cursor", cursor)]);
+        }
+        let list: StrikesList = send(request).await?.json().await?;
+        strikes.extend(list.strikes);
+        match list.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    strikes.sort_by_key(|entry| std::cmp::Reverse(entry.amount));
+    Ok(strikes)
+}
+
+pub async fn clear_strikes(token: &str, ip: &str) -> Result<(), ApiError> {
+    let path = format!("/strikes/{}", encode_segment(ip));
+    send(v1_request(reqwest::Method::PUT, &path, token).json(&serde_json::json!({ "amount": 0 })))
+        .await?;
+    Ok(())
+}
+
+/// Moderation actions on a link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkAction {
+    Quarantine,
+    Release,
+    Disable,
+    Enable,
+}
+
+impl LinkAction {
+    pub const ALL: [Self; 4] = [Self::Quarantine, Self::Release, Self::Disable, Self::Enable];
+
+    fn endpoint(self) -> &'static str {
+        match self {
+            Self::Quarantine => "quarantine",
+            Self::Release => "release",
+            Self::Disable => "disable",
+            Self::Enable => "enable",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Quarantine => "Quarantine",
+            Self::Release => "Release",
+            Self::Disable => "Disable",
+            Self::Enable => "Enable",
+        }
+    }
+}
+
+pub async fn moderate_link(token: &str, id: &str, action: LinkAction) -> Result<(), ApiError> {
+    let path = format!("/links/{}/{}", encode_segment(id), action.endpoint());
+    send_admin(v1_request(reqwest::Method::POST, &path, token), "Link").await?;
+    Ok(())
+}
+
+/// Full configuration info, displayed as it is
+pub async fn raw_config_info() -> Result<serde_json::Value, ApiError> {
+    Ok(send(
+        request(reqwest::Method::GET, "/config_info", None)
+            .header(header::ACCEPT, "application/json"),
+    )
+    .await?
+    .json()
+    .await?)
+}
+
+/// URL of the QR code image of the link in the format, `svg` or `png`
+pub fn qr_code_url(id: &str, format: &str) -> String {
+    format!(
+        "{}{API_PREFIX}/{}/qr?format={format}",
+        origin(),
+        encode_segment(id)
+    )
+}
+
+/// Percent-encodes characters that can't appear in a path segment as they are
+fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
@@ -0,0 +1,77 @@
+use dioxus::prelude::*;
+
+use crate::{
+    admin::Admin, api, link_info::LinkInfoView, session::Session, session::TokenInput,
+    shorten::Shorten,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Shorten,
+    LinkInfo,
+    Admin,
+}
+
+impl View {
+    const ALL: [Self; 3] = [Self::Shorten, Self::LinkInfo, Self::Admin];
+
+    /// Whether the view is shown, the admin section needs the token system
+    fn available(self, tokens_enabled: bool) -> bool {
+        self != Self::Admin || tokens_enabled
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Shorten => "Shorten",
+            Self::LinkInfo => "Link info",
+            Self::Admin => "Admin",
+        }
+    }
+}
+
+pub fn App(cx: Scope) -> Element {
+    use_shared_state_provider(cx, Session::default);
+    let view = use_state(cx, || View::Shorten);
+    let config = use_future(cx, (), |_| api::config_info());
+
+    let (tokens_enabled, requires_auth) = match config.value() {
+        Some(Ok(config)) => (
+            config.tokens.is_some(),
+            config.creation_requires_auth(&api::hostname()),
+        ),
+        _ => (false, false),
+    };
+    let config_error = match config.value() {
+        Some(Err(err)) => {
+            Some(rsx! { p { class: "error", "Loading configuration failed: {err}" } })
+        }
+        _ => None,
+    };
+
+    render! {
+        header {
+            h1 { "Sayless" }
+            nav {
+                for tab in View::ALL.into_iter().filter(|tab| tab.available(tokens_enabled)) {
+                    button {
+                        key: "{tab.title()}",
+                        class: if *view.get() == tab { "active" } else { "" },
+                        onclick: move |_| view.set(tab),
+                        tab.title()
+                    }
+                }
+            }
+            if tokens_enabled {
+                rsx! { TokenInput { required: requires_auth } }
+            }
+        }
+        main {
+            config_error
+            match view.get() {
+                View::Shorten => rsx! { Shorten { requires_auth: requires_auth } },
+                View::LinkInfo => rsx! { LinkInfoView {} },
+                View::Admin => rsx! { Admin {} },
+            }
+        }
+    }
+}
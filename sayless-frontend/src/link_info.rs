@@ -0,0 +1,123 @@
+use dioxus::prelude::*;
+
+use crate::{
+    api::{self, ApiError, LinkInfo},
+    session::Session,
+};
+
+/// Looks up information about a short link by its id
+pub fn LinkInfoView(cx: Scope) -> Element {
+    let session = use_shared_state::<Session>(cx)?;
+    let id = use_state(cx, String::new);
+    let pending = use_state(cx, || false);
+    let result = use_state(cx, || None::<Result<LinkInfo, ApiError>>);
+
+    let submit = move |_| {
+        let token = session.read().token.clone();
+        // Accept full short links as well as ids
+        let id = id
+            .get()
+            .trim()
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        to_owned![pending, result];
+        cx.spawn(async move {
+            pending.set(true);
+            result.set(Some(api::link_info(token.as_deref(), &id).await));
+            pending.set(false);
+        });
+    };
+
+    let result_view = match result.get() {
+        Some(Ok(info)) => Some(rsx! { LinkDetails { info: info.clone() } }),
+        Some(Err(err)) => Some(rsx! { p { class: "error", "{err}" } }),
+        None => None,
+    };
+
+    render! {
+        form { class: "lookup", prevent_default: "onsubmit", onsubmit: submit,
+            input {
+                required: true,
+                placeholder: "Short link or id",
+                value: "{id}",
+                oninput: move |evt| id.set(evt.value.clone())
+            }
+            button { r#type: "submit", disabled: *pending.get(), "Look up" }
+        }
+        result_view
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "Yes"
+    } else {
+        "No"
+    }
+}
+
+#[derive(Props, PartialEq)]
+struct LinkDetailsProps {
+    info: LinkInfo,
+}
+
+fn LinkDetails(cx: Scope<LinkDetailsProps>) -> Element {
+    let info = &cx.props.info;
+    let destination = info
+        .link
+        .as_deref()
+        .unwrap_or("Hidden, the link is password-protected");
+    let status = match (&info.quarantined_at, &info.disabled_at) {
+        (Some(quarantined_at), _) => format!("Quarantined at {quarantined_at}"),
+        (None, Some(disabled_at)) => format!("Disabled at {disabled_at}"),
+        (None, None) => "Active".to_owned(),
+    };
+    let redirect_code = info
+        .redirect_code
+        .map_or_else(|| "Default".to_owned(), |code| code.to_string());
+
+    render! {
+        dl { class: "details",
+            dt { "Id" }
+            dd { "{info.id}" }
+            dt { "Destination" }
+            dd { "{destination}" }
+            dt { "Created" }
+            dd { "{info.created_at}" }
+            dt { "Status" }
+            dd { "{status}" }
+            dt { "Password-protected" }
+            dd { yes_no(info.password_protected) }
+            dt { "Editable" }
+            dd { yes_no(info.editable) }
+            dt { "Redirect code" }
+            dd { "{redirect_code}" }
+            dt { "Forwards query" }
+            dd { yes_no(info.forward_query) }
+            dt { "Forwards path" }
+            dd { yes_no(info.forward_path) }
+        }
+        if !info.revisions.is_empty() {
+            rsx! {
+                h3 { "Revisions" }
+                table { class: "revisions",
+                    tr {
+                        th { "Changed" }
+                        th { "From" }
+                        th { "To" }
+                    }
+                    for revision in &info.revisions {
+                        tr { key: "{revision.id}",
+                            td { "{revision.changed_at}" }
+                            td { "{revision.previous_link}" }
+                            td { "{revision.link}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
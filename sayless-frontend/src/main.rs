@@ -1,3 +1,13 @@
+#![allow(non_snake_case)]
+
+mod admin;
+mod api;
+mod app;
+mod link_info;
+mod qr;
+mod session;
+mod shorten;
+
 fn main() {
-    println!("Hello, world!");
+    dioxus_web::launch(app::App);
 }
@@ -0,0 +1,25 @@
+use dioxus::prelude::*;
+
+use crate::api;
+
+#[derive(Props, PartialEq)]
+pub struct QrCodeImageProps {
+    id: String,
+}
+
+/// QR code of the short link with the id, rendered by the server
+pub fn QrCodeImage(cx: Scope<QrCodeImageProps>) -> Element {
+    let svg = api::qr_code_url(&cx.props.id, "svg");
+    let png = api::qr_code_url(&cx.props.id, "png");
+
+    render! {
+        div { class: "qr",
+            img { src: "{svg}", width: 200, height: 200, alt: "QR code of the short link" }
+            p {
+                a { href: "{svg}", download: "{cx.props.id}.svg", "SVG" }
+                " · "
+                a { href: "{png}&size=1024", download: "{cx.props.id}.png", "PNG" }
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use dioxus::prelude::*;
+
+/// State shared by all views
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    /// Token sent with API requests, entered by the user
+    pub token: Option<String>,
+}
+
+#[derive(Props, PartialEq)]
+pub struct TokenInputProps {
+    required: bool,
+}
+
+/// Entry of the token sent with API requests
+pub fn TokenInput(cx: Scope<TokenInputProps>) -> Element {
+    let session = use_shared_state::<Session>(cx)?;
+    let token = session.read().token.clone().unwrap_or_default();
+
+    render! {
+        label { class: "token",
+            if cx.props.required { "Token (required)" } else { "Token" }
+            input {
+                r#type: "password",
+                autocomplete: "off",
+                value: "{token}",
+                oninput: move |evt| {
+                    let token = evt.value.trim().to_owned();
+                    session.write().token = (!token.is_empty()).then_some(token);
+                }
+            }
+        }
+    }
+}
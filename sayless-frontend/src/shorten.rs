@@ -0,0 +1,101 @@
+use dioxus::prelude::*;
+
+use crate::{
+    api::{self, ApiError},
+    qr::QrCodeImage,
+    session::Session,
+};
+
+#[derive(Props, PartialEq)]
+pub struct ShortenProps {
+    requires_auth: bool,
+}
+
+/// Form shortening a link, showing the short link once it's created
+pub fn Shorten(cx: Scope<ShortenProps>) -> Element {
+    let session = use_shared_state::<Session>(cx)?;
+    let url = use_state(cx, String::new);
+    let password = use_state(cx, String::new);
+    let pending = use_state(cx, || false);
+    let result = use_state(cx, || None::<Result<String, ApiError>>);
+
+    let submit = move |_| {
+        let token = session.read().token.clone();
+        if cx.props.requires_auth && token.is_none() {
+            result.set(Some(Err(ApiError(
+                "A token is required to shorten links".to_owned(),
+            ))));
+            return;
+        }
+        let url = url.get().trim().to_owned();
+        let password = Some(password.get().clone()).filter(|password| !password.is_empty());
+        to_owned![pending, result];
+        cx.spawn(async move {
+            pending.set(true);
+            result.set(Some(
+                api::create_link(token.as_deref(), &url, password.as_deref()).await,
+            ));
+            pending.set(false);
+        });
+    };
+
+    let result_view = match result.get() {
+        // Keyed by the link, so that a new link starts without the state of the previous one
+        Some(Ok(link)) => Some(rsx! { ShortLink { key: "{link}", link: link.clone() } }),
+        Some(Err(err)) => Some(rsx! { p { class: "error", "{err}" } }),
+        None => None,
+    };
+
+    render! {
+        form { class: "shorten", prevent_default: "onsubmit", onsubmit: submit,
+            input {
+                r#type: "url",
+                required: true,
+                placeholder: "https://example.com/a/long/link",
+                value: "{url}",
+                oninput: move |evt| url.set(evt.value.clone())
+            }
+            input {
+                r#type: "password",
+                autocomplete: "new-password",
+                placeholder: "Password (optional)",
+                value: "{password}",
+                oninput: move |evt| password.set(evt.value.clone())
+            }
+            button { r#type: "submit", disabled: *pending.get(), "Shorten" }
+        }
+        result_view
+    }
+}
+
+#[derive(Props, PartialEq)]
+struct ShortLinkProps {
+    link: String,
+}
+
+/// Created short link with copy and QR code buttons
+fn ShortLink(cx: Scope<ShortLinkProps>) -> Element {
+    let link = &cx.props.link;
+    let id = link.rsplit('/').next().unwrap_or_default();
+    let eval = use_eval(cx);
+    let copied = use_state(cx, || false);
+    let show_qr = use_state(cx, || false);
+
+    let copy = move |_| {
+        let link = serde_json::to_string(link).expect("Strings are always serializable");
+        if eval(&format!("navigator.clipboard.writeText({link})")).is_ok() {
+            copied.set(true);
+        }
+    };
+
+    render! {
+        div { class: "result",
+            a { href: "{link}", target: "_blank", "{link}" }
+            button { onclick: copy, if *copied.get() { "Copied" } else { "Copy" } }
+            button { onclick: move |_| show_qr.set(!show_qr.get()), "QR code" }
+        }
+        if *show_qr.get() {
+            rsx! { QrCodeImage { id: id.to_owned() } }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::{fmt, net::IpAddr, str::FromStr};
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{MySql, Pool};
+
+/// Administrative and security-relevant actions recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    TokenCreated,
+    TokenRevoked,
+    StrikesSet,
+    StrikesAdded,
+    LinkUpdated,
+    LinkReverted,
+    LinkDisabled,
+    LinkEnabled,
+    LinkQuarantined,
+    LinkReleased,
+    LinkDeleted,
+    MaintenanceJobRun,
+}
+
+impl AuditAction {
+    /// Name of the action, as stored in the database and shown by the API
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TokenCreated => "token_created",
+            Self::TokenRevoked => "token_revoked",
+            Self::StrikesSet => "strikes_set",
+            Self::StrikesAdded => "strikes_added",
+            Self::LinkUpdated => "link_updated",
+            Self::LinkReverted => "link_reverted",
+            Self::LinkDisabled => "link_disabled",
+            Self::LinkEnabled => "link_enabled",
+            Self::LinkQuarantined => "link_quarantined",
+            Self::LinkReleased => "link_released",
+            Self::LinkDeleted => "link_deleted",
+            Self::MaintenanceJobRun => "maintenance_job_run",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        [
+            Self::TokenCreated,
+            Self::TokenRevoked,
+            Self::StrikesSet,
+            Self::StrikesAdded,
+            Self::LinkUpdated,
+            Self::LinkReverted,
+            Self::LinkDisabled,
+            Self::LinkEnabled,
+            Self::LinkQuarantined,
+            Self::LinkReleased,
+            Self::LinkDeleted,
+            Self::MaintenanceJobRun,
+        ]
+        .into_iter()
+        .find(|known| known.as_str() == action)
+        .ok_or_else(|| format!("Unknown audit action `{action}`"))
+    }
+}
+
+/// What an action was done to
+#[derive(Debug, Clone, Copy)]
+pub enum AuditTarget<'a> {
+    Token(u64),
+    Ip(IpAddr),
+    Link {
+        namespace: &'a str,
+        id: &'a str,
+    },
+    /// Name of a maintenance job
+    Job(&'a str),
+}
+
+impl AuditTarget<'_> {
+    /// Target column, and the namespace of links
+    fn columns(&self) -> (String, Option<&str>) {
+        match self {
+            Self::Token(id) => (id.to_string(), None),
+            Self::Ip(ip) => (ip.to_string(), None),
+            Self::Link { namespace, id } => ((*id).to_owned(), Some(namespace)),
+            Self::Job(name) => ((*name).to_owned(), None),
+        }
+    }
+}
+
+/// Appends an event to the audit log. `actor_token` is `None` for the master token, and
+/// `client_ip` should only be set with IP recording. The action has already happened when it's
+/// recorded, so a failed insert is logged with the whole event instead of failing the request.
+#[tracing::instrument(skip_all)]
+pub async fn record_event(
+    db: &Pool<MySql>,
+    actor_token: Option<u64>,
+    action: AuditAction,
+    target: AuditTarget<'_>,
+    client_ip: Option<IpAddr>,
+) {
+    let (target_column, target_domain) = target.columns();
+    let client_ip_bytes = match client_ip.map(|ip| bincode::serialize(&ip)).transpose() {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("Error serializing client ip of audit event: {e}");
+            None
+        }
+    };
+
+    if let Err(e) = sqlx::query!(
+        r#"
+        INSERT INTO audit_events (actor_token, action, target, target_domain, client_ip)
+        values (?, ?, ?, ?, ?)
+        "#,
+        actor_token,
+        action.as_str(),
+        &target_column,
+        target_domain,
+        client_ip_bytes
+    )
+    .execute(db)
+    .await
+    {
+        log::error!(
+            "Error recording audit event {action} of {target:?} by {}: {e}",
+            actor_token.map_or_else(|| "master".to_owned(), |id| format!("token {id}"))
+        );
+    }
+}
+
+/// Removes client IPs of audit events older than the IP retention period, the events themselves
+/// are kept for the audit log retention period. Returns how many were removed.
+#[tracing::instrument(skip_all)]
+pub async fn purge_expired_client_ips(
+    db: &Pool<MySql>,
+    retention_period: Duration,
+) -> Result<u64, sqlx::Error> {
+    // Nothing is older than a period reaching before the earliest date
+    let Some(expired_date) = Utc::now().checked_sub_signed(retention_period) else {
+        return Ok(0);
+    };
+    Ok(sqlx::query!(
+        "UPDATE audit_events SET client_ip = NULL WHERE client_ip IS NOT NULL AND created_at < ?",
+        expired_date
+    )
+    .execute(db)
+    .await?
+    .rows_affected())
+}
+
+/// Removes audit events older than the retention period. Returns how many were removed.
+#[tracing::instrument(skip_all)]
+pub async fn purge_expired_events(
+    db: &Pool<MySql>,
+    retention_period: Duration,
+) -> Result<u64, sqlx::Error> {
+    // Nothing is older than a period reaching before the earliest date
+    let Some(expired_date) = Utc::now().checked_sub_signed(retention_period) else {
+        return Ok(0);
+    };
+    Ok(sqlx::query!(
+        "DELETE FROM audit_events WHERE created_at < ?",
+        expired_date
+    )
+    .execute(db)
+    .await?
+    .rows_affected())
+}
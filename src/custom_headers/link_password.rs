@@ -0,0 +1,35 @@
+use axum::http::{HeaderName, HeaderValue};
+use headers::Header;
+
+static LINK_PASSWORD: HeaderName = HeaderName::from_static("x-link-password");
+
+/// Password that protects created links
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkPassword(pub(crate) String);
+
+impl Header for LinkPassword {
+    fn name() -> &'static HeaderName {
+        &LINK_PASSWORD
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        values
+            .next()
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.is_empty())
+            .map(|v| LinkPassword(v.to_string()))
+            .ok_or_else(headers::Error::invalid)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value = self
+            .0
+            .parse()
+            .expect("LinkPassword is decoded from a valid HeaderValue");
+        values.extend(std::iter::once(value));
+    }
+}
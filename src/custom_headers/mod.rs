@@ -1 +1,2 @@
 pub mod accept;
+pub mod link_password;
@@ -1,11 +1,16 @@
 use sqlx::{mysql::MySqlPoolOptions, MySql, Pool};
 
+use crate::secrets::read_secret;
+
+/// Connects to the database at `DATABASE_URL`, or at the URL in the file at `DATABASE_URL_FILE`
 pub async fn connect_db() -> Result<Pool<MySql>, sqlx::Error> {
+    let url = read_secret("DATABASE_URL")
+        .map_err(sqlx::Error::Configuration)?
+        .ok_or_else(|| {
+            sqlx::Error::Configuration(
+                "DATABASE_URL or DATABASE_URL_FILE must be set to connect to the database".into(),
+            )
+        })?;
     log::info!("connecting to db");
-    MySqlPoolOptions::new()
-        .connect(
-            &dotenvy::var("DATABASE_URL")
-                .expect("DATABASE_URL must be set to connect to the database"),
-        )
-        .await
+    MySqlPoolOptions::new().connect(&url).await
 }
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    create_token_params::{deser_timestamp, ser_timestamp},
+    redirect_code::RedirectCode,
+};
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CreateLinkParams {
+    /// Destination of the link can be changed later by its owner
+    #[serde(default)]
+    pub editable: bool,
+    /// Hash of the password protecting the link, supplied via `X-Link-Password` header
+    #[serde(skip)]
+    pub password_hash: Option<String>,
+    /// Overrides the configured default redirect code
+    #[serde(default)]
+    pub redirect_code: Option<RedirectCode>,
+    /// Append the query string of the short link request to the destination
+    #[serde(default)]
+    pub forward_query: bool,
+    /// Append the path after the id (`/l/:id/extra/path`) to the destination
+    #[serde(default)]
+    pub forward_path: bool,
+    /// `max-age` of `Cache-Control` header of permanent redirects, in seconds. Overrides the
+    /// configured default
+    #[serde(default)]
+    pub cache_max_age: Option<u32>,
+    /// The link stops redirecting at this time, and is deleted by the `expired_links`
+    /// maintenance job
+    #[serde(
+        default,
+        deserialize_with = "deser_timestamp",
+        serialize_with = "ser_timestamp",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_at: Option<DateTime<Utc>>,
+}
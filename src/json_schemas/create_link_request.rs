@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use super::create_link_params::CreateLinkParams;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateLinkRequest {
+    pub url: String,
+    /// Protects the link with a password
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(flatten)]
+    pub params: CreateLinkParams,
+}
@@ -1,20 +1,38 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::token_permissions::TokenPermissions;
 
-#[derive(Deserialize)]
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+#[derive(Deserialize, Serialize)]
 pub struct CreateTokenParams {
     #[serde(flatten, default)]
     pub perms: TokenPermissions,
-    #[serde(default, deserialize_with = "deser_timestamp")]
+    #[serde(
+        default,
+        deserialize_with = "deser_timestamp",
+        serialize_with = "ser_timestamp",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub expires_at: Option<DateTime<Utc>>,
 }
 
-fn deser_timestamp<'de, D: Deserializer<'de>>(des: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+pub(super) fn deser_timestamp<'de, D: Deserializer<'de>>(
+    des: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
     Ok(Some(
-        NaiveDateTime::parse_from_str(&String::deserialize(des)?, "%Y-%m-%d %H:%M:%S%.f")
+        NaiveDateTime::parse_from_str(&String::deserialize(des)?, TIMESTAMP_FORMAT)
             .map_err(serde::de::Error::custom)?
             .and_utc(),
     ))
 }
+
+pub(super) fn ser_timestamp<S: Serializer>(
+    timestamp: &Option<DateTime<Utc>>,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    timestamp
+        .map(|timestamp| timestamp.format(TIMESTAMP_FORMAT).to_string())
+        .serialize(ser)
+}
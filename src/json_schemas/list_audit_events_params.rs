@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::create_token_params::deser_timestamp;
+use crate::audit::AuditAction;
+
+#[derive(Debug, Deserialize)]
+pub struct ListAuditEventsParams {
+    /// Id of the last event on the previous page
+    #[serde(default)]
+    pub cursor: Option<u64>,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    /// `master` or a token id
+    #[serde(default)]
+    pub actor: Option<String>,
+    #[serde(default)]
+    pub action: Option<AuditAction>,
+    /// Token id, IP or link id
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Namespace of link targets
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default, deserialize_with = "deser_timestamp")]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deser_timestamp")]
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+const fn default_limit() -> u32 {
+    50
+}
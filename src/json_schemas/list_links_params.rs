@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::create_token_params::deser_timestamp;
+
+#[derive(Debug, Deserialize)]
+pub struct ListLinksParams {
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    #[serde(default, deserialize_with = "deser_timestamp")]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deser_timestamp")]
+    pub created_before: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Only links owned by this token id. Admin only, unless it's the caller's own id
+    #[serde(default)]
+    pub owner: Option<u64>,
+    /// List links of all owners. Admin only
+    #[serde(default)]
+    pub all: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+const fn default_limit() -> u32 {
+    50
+}
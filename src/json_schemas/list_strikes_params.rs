@@ -0,0 +1,19 @@
+use std::net::IpAddr;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ListStrikesParams {
+    /// Last IP of the previous page
+    #[serde(default)]
+    pub cursor: Option<IpAddr>,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    /// Only IPs containing this text
+    #[serde(default)]
+    pub search: Option<String>,
+}
+
+const fn default_limit() -> u32 {
+    50
+}
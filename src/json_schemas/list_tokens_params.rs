@@ -0,0 +1,17 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ListTokensParams {
+    /// Id of the last token on the previous page
+    #[serde(default)]
+    pub cursor: Option<u64>,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+    /// Include expired tokens
+    #[serde(default)]
+    pub all: bool,
+}
+
+const fn default_limit() -> u32 {
+    50
+}
@@ -1,2 +1,14 @@
+pub mod create_link_params;
+pub mod create_link_request;
+pub mod create_links_request;
 pub mod create_token_params;
+pub mod list_audit_events_params;
+pub mod list_links_params;
+pub mod list_strikes_params;
+pub mod list_tokens_params;
+pub mod qr_params;
+pub mod redirect_code;
+pub mod revoke_token_request;
+pub mod set_strikes_request;
 pub mod token_permissions;
+pub mod update_link_request;
@@ -0,0 +1,84 @@
+use std::fmt;
+
+use serde::{de, Deserialize, Deserializer};
+
+/// Options of QR code images, all optional
+#[derive(Debug, Deserialize)]
+pub struct QrParams {
+    /// Overrides the format selected through `Accept`
+    #[serde(default)]
+    pub format: Option<QrFormat>,
+    /// Width and height in pixels
+    #[serde(default = "default_size")]
+    pub size: u32,
+    #[serde(default)]
+    pub ecc: QrErrorCorrection,
+    /// Width of the quiet zone around the code, in modules
+    #[serde(default = "default_margin")]
+    pub margin: u32,
+    #[serde(default = "QrColor::black")]
+    pub fg: QrColor,
+    #[serde(default = "QrColor::white")]
+    pub bg: QrColor,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QrFormat {
+    Svg,
+    Png,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub enum QrErrorCorrection {
+    L,
+    #[default]
+    M,
+    Q,
+    H,
+}
+
+/// RGB colour, written as `rrggbb` with an optional leading `#`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QrColor(pub [u8; 3]);
+
+impl QrColor {
+    const fn black() -> Self {
+        Self([0, 0, 0])
+    }
+
+    const fn white() -> Self {
+        Self([255, 255, 255])
+    }
+}
+
+impl fmt::Display for QrColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+impl<'de> Deserialize<'de> for QrColor {
+    fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(des)?;
+        let hex = value.strip_prefix('#').unwrap_or(&value);
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(de::Error::custom("colour must be `rrggbb`"));
+        }
+        let mut rgb = [0; 3];
+        for (i, channel) in rgb.iter_mut().enumerate() {
+            *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|_| de::Error::custom("colour must be `rrggbb`"))?;
+        }
+        Ok(Self(rgb))
+    }
+}
+
+const fn default_size() -> u32 {
+    256
+}
+
+const fn default_margin() -> u32 {
+    4
+}
@@ -0,0 +1,49 @@
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// HTTP status code used for redirecting to the link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct RedirectCode(u16);
+
+impl RedirectCode {
+    pub const MOVED_PERMANENTLY: Self = Self(301);
+    pub const FOUND: Self = Self(302);
+    pub const SEE_OTHER: Self = Self(303);
+    pub const TEMPORARY_REDIRECT: Self = Self(307);
+    pub const PERMANENT_REDIRECT: Self = Self(308);
+
+    pub fn status(self) -> StatusCode {
+        StatusCode::from_u16(self.0).expect("Redirect code is always a valid status code")
+    }
+
+    /// Permanent redirects are cached by clients
+    pub fn is_permanent(self) -> bool {
+        self == Self::MOVED_PERMANENTLY || self == Self::PERMANENT_REDIRECT
+    }
+}
+
+impl Default for RedirectCode {
+    fn default() -> Self {
+        Self::FOUND
+    }
+}
+
+impl TryFrom<u16> for RedirectCode {
+    type Error = String;
+
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        match code {
+            301 | 302 | 303 | 307 | 308 => Ok(Self(code)),
+            _ => Err(format!(
+                "Invalid redirect code {code}, expected one of 301, 302, 303, 307, 308"
+            )),
+        }
+    }
+}
+
+impl From<RedirectCode> for u16 {
+    fn from(code: RedirectCode) -> Self {
+        code.0
+    }
+}
@@ -1,2 +1,40 @@
+use std::sync::Arc;
+
+use sqlx::{migrate::Migrator, MySql, Pool};
+
+use crate::{
+    links::{cache::LinkCache, password::FailedAttempts},
+    maintenance::Maintenance,
+    service_config::reload::SharedConfig,
+};
+
+pub mod audit;
+pub mod base58;
+pub mod custom_headers;
+pub mod database;
 pub mod json_schemas;
+pub mod links;
+pub mod logging;
+pub mod maintenance;
+pub mod responses;
+pub mod routes;
+pub mod secrets;
+pub mod server;
+pub mod service;
+pub mod service_config;
+pub mod strikes;
 pub mod tokens;
+
+pub type DbPool = Arc<Pool<MySql>>;
+
+/// Migrations of the database schema, applied by the server on startup
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Clone)]
+pub struct ServiceState {
+    pub db: DbPool,
+    pub config: SharedConfig,
+    pub password_attempts: Arc<FailedAttempts>,
+    pub link_cache: Arc<LinkCache>,
+    pub maintenance: Arc<Maintenance>,
+}
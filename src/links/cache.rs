@@ -0,0 +1,175 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+
+use super::redirect::RedirectOptions;
+use crate::service_config::redirect_cache::RedirectCacheConfig;
+
+/// Link data needed to serve a redirect
+#[derive(Debug, Clone)]
+pub struct CachedLink {
+    pub link: String,
+    pub password_hash: Option<String>,
+    pub options: RedirectOptions,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Result of looking up a link id. Missing, deleted and disabled links are cached as the status
+/// code they are answered with.
+pub type LinkLookup = Result<CachedLink, StatusCode>;
+
+/// Ids are only unique within a domain namespace. Namespaces are host names, which can't contain
+/// `/`.
+fn cache_key(namespace: &str, id: &str) -> String {
+    format!("{namespace}/{id}")
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    lookup: LinkLookup,
+    inserted_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheInner {
+    entries: HashMap<String, CacheEntry>,
+    /// Cache keys by the tick they were last used at, least recently used first
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    /// Incremented by every invalidation
+    generation: u64,
+}
+
+impl CacheInner {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.recency.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.recency.insert(tick, key.to_owned());
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.last_used);
+        }
+    }
+}
+
+/// Bounded LRU cache of link lookups with a TTL, kept in front of the database for redirects
+#[derive(Debug)]
+pub struct LinkCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<CacheInner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LinkCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl LinkCache {
+    pub fn new(config: &RedirectCacheConfig) -> Self {
+        Self {
+            capacity: config.capacity,
+            ttl: config.ttl.to_std().unwrap_or_default(),
+            inner: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.capacity > 0 && !self.ttl.is_zero()
+    }
+
+    /// Returns the cached lookup of the link, if there is one that hasn't expired
+    pub fn get(&self, namespace: &str, id: &str) -> Option<LinkLookup> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let key = cache_key(namespace, id);
+        let mut inner = self.inner.lock().unwrap();
+        let lookup = match inner.entries.get(&key) {
+            Some(entry) if entry.inserted_at.elapsed() < self.ttl => Some(entry.lookup.clone()),
+            Some(_) => {
+                inner.remove(&key);
+                None
+            }
+            None => None,
+        };
+        if lookup.is_some() {
+            inner.touch(&key);
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        lookup
+    }
+
+    /// Current generation, to be read before looking up a link to pass it to `insert`
+    pub fn generation(&self) -> u64 {
+        self.inner.lock().unwrap().generation
+    }
+
+    /// Caches the lookup of the link, evicting the least recently used link if the cache is full.
+    /// The lookup is dropped if any link was invalidated since `generation`, as it may have been
+    /// read before the change.
+    pub fn insert(&self, namespace: &str, id: &str, lookup: LinkLookup, generation: u64) {
+        if !self.is_enabled() {
+            return;
+        }
+        let key = cache_key(namespace, id);
+        let mut inner = self.inner.lock().unwrap();
+        if inner.generation != generation {
+            return;
+        }
+        inner.remove(&key);
+        while inner.entries.len() >= self.capacity {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+        inner.entries.insert(
+            key.clone(),
+            CacheEntry {
+                lookup,
+                inserted_at: Instant::now(),
+                last_used: 0,
+            },
+        );
+        inner.touch(&key);
+    }
+
+    /// Drops the cached lookup of the link, called whenever the link changes
+    pub fn invalidate(&self, namespace: &str, id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner.remove(&cache_key(namespace, id));
+    }
+
+    pub fn stats(&self) -> LinkCacheStats {
+        LinkCacheStats {
+            entries: self.inner.lock().unwrap().entries.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
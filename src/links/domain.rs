@@ -0,0 +1,43 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, StatusCode, Uri},
+};
+
+use crate::{service_config::domains::DomainConfig, ServiceState};
+
+/// Short domain the request was made to. Requests to hosts that aren't configured are rejected
+/// with 421.
+#[derive(Debug, Clone)]
+pub struct LinkDomain(pub DomainConfig);
+
+impl FromRequestParts<ServiceState> for LinkDomain {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &ServiceState,
+    ) -> Result<Self, Self::Rejection> {
+        state
+            .config
+            .load()
+            .domain(request_host(&parts.headers, &parts.uri).map(strip_port))
+            .map(LinkDomain)
+            .ok_or(StatusCode::MISDIRECTED_REQUEST)
+    }
+}
+
+/// Host the request was made to, from the `Host` header or the authority of HTTP/2 requests.
+/// Forwarding headers like `X-Forwarded-Host` are ignored, any client can set them.
+pub fn request_host<'a>(headers: &'a HeaderMap, uri: &'a Uri) -> Option<&'a str> {
+    headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .or_else(|| uri.authority().map(|authority| authority.as_str()))
+}
+
+fn strip_port(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((name, port)) if !name.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    }
+}
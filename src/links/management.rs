@@ -0,0 +1,359 @@
+use axum::http::{StatusCode, Uri};
+use chrono::{DateTime, Utc};
+use sqlx::{MySql, MySqlConnection, Pool, Transaction};
+
+use super::{is_deleted, parse_link};
+use crate::tokens::TokenIdentity;
+
+#[derive(Debug)]
+struct ManagedLinkQuery {
+    link: String,
+    editable: bool,
+    created_by_token: Option<u64>,
+    disabled_at: Option<DateTime<Utc>>,
+}
+
+async fn begin(db: &Pool<MySql>) -> Result<Transaction<'static, MySql>, StatusCode> {
+    db.begin().await.map_err(|e| {
+        log::error!("Error starting link management transaction: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+async fn commit(tx: Transaction<'static, MySql>) -> Result<(), StatusCode> {
+    tx.commit().await.map_err(|e| {
+        log::error!("Error committing link management transaction: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Locks the link for the rest of the transaction. Only the owner of the link or an admin can
+/// manage it.
+#[tracing::instrument(skip_all)]
+async fn lock_managed_link(
+    conn: &mut MySqlConnection,
+    namespace: &str,
+    id: &str,
+    actor: &TokenIdentity,
+) -> Result<ManagedLinkQuery, StatusCode> {
+    let Some(link) = sqlx::query_as!(
+        ManagedLinkQuery,
+        r#"
+        SELECT link, editable AS `editable: _`, created_by_token, disabled_at
+        FROM links
+        WHERE domain = ? AND id = ?
+        FOR UPDATE
+        "#,
+        namespace,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        log::error!("Error looking up link `{id}` for update: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    else {
+        return Err(if is_deleted(conn, namespace, id).await? {
+            StatusCode::GONE
+        } else {
+            StatusCode::NOT_FOUND
+        });
+    };
+
+    if !actor.is_admin() && (actor.id().is_none() || actor.id() != link.created_by_token) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(link)
+}
+
+/// Changes the destination of an editable link and records the change as a revision
+#[tracing::instrument(skip_all)]
+pub async fn change_link_destination(
+    db: &Pool<MySql>,
+    namespace: &str,
+    id: &str,
+    uri: &Uri,
+    actor: TokenIdentity,
+) -> Result<(), StatusCode> {
+    let mut tx = begin(db).await?;
+
+    let ManagedLinkQuery {
+        link: previous_link,
+        editable,
+        disabled_at,
+        ..
+    } = lock_managed_link(&mut tx, namespace, id, &actor).await?;
+
+    if !editable {
+        return Err(StatusCode::CONFLICT);
+    }
+    if disabled_at.is_some() {
+        return Err(StatusCode::GONE);
+    }
+
+    let link = uri.to_string();
+    let uri_hash: [u8; 32] = blake3::hash(link.as_bytes()).into();
+
+    sqlx::query!(
+        "UPDATE links SET link = ?, hash = ? WHERE domain = ? AND id = ?",
+        &link,
+        uri_hash.as_ref(),
+        namespace,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error updating link `{id}` destination: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO link_revisions (link_domain, link_id, previous_link, link, actor_token)
+        values (?, ?, ?, ?, ?)
+        "#,
+        namespace,
+        id,
+        previous_link,
+        &link,
+        actor.id()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error recording revision of link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    commit(tx).await
+}
+
+#[derive(Debug)]
+struct RevisionQuery {
+    previous_link: String,
+}
+
+/// Undoes a revision by setting the destination back to what it was before the revision. The
+/// revert itself is recorded as a new revision.
+#[tracing::instrument(skip_all)]
+pub async fn revert_link_revision(
+    db: &Pool<MySql>,
+    namespace: &str,
+    id: &str,
+    revision: u64,
+    actor: TokenIdentity,
+) -> Result<(), StatusCode> {
+    let RevisionQuery { previous_link } = sqlx::query_as!(
+        RevisionQuery,
+        "SELECT previous_link FROM link_revisions WHERE id = ? AND link_domain = ? AND link_id = ?",
+        revision,
+        namespace,
+        id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        log::error!("Error looking up revision {revision} of link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let uri = parse_link(&previous_link).ok_or_else(|| {
+        log::error!("Revision {revision} of link `{id}` has an invalid link");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    change_link_destination(db, namespace, id, &uri, actor).await
+}
+
+/// Stops the link from redirecting. The hash is removed, so that the same destination can't be
+/// shortened back to this id.
+#[tracing::instrument(skip_all)]
+pub async fn disable_link(
+    db: &Pool<MySql>,
+    namespace: &str,
+    id: &str,
+    actor: TokenIdentity,
+) -> Result<(), StatusCode> {
+    let mut tx = begin(db).await?;
+
+    let ManagedLinkQuery { disabled_at, .. } =
+        lock_managed_link(&mut tx, namespace, id, &actor).await?;
+    if disabled_at.is_some() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "UPDATE links SET hash = NULL, disabled_at = CURRENT_TIMESTAMP WHERE domain = ? AND id = ?",
+        namespace,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error disabling link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    commit(tx).await
+}
+
+/// Makes a disabled link redirect again
+#[tracing::instrument(skip_all)]
+pub async fn enable_link(
+    db: &Pool<MySql>,
+    namespace: &str,
+    id: &str,
+    actor: TokenIdentity,
+) -> Result<(), StatusCode> {
+    let mut tx = begin(db).await?;
+
+    let ManagedLinkQuery {
+        link, disabled_at, ..
+    } = lock_managed_link(&mut tx, namespace, id, &actor).await?;
+    if disabled_at.is_none() {
+        return Ok(());
+    }
+
+    let uri_hash: [u8; 32] = blake3::hash(link.as_bytes()).into();
+
+    sqlx::query!(
+        "UPDATE links SET hash = ?, disabled_at = NULL WHERE domain = ? AND id = ?",
+        uri_hash.as_ref(),
+        namespace,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error enabling link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    commit(tx).await
+}
+
+/// Hides the link from everyone but admins until it's released. Unlike disabling, the owner of
+/// the link can't undo it.
+pub async fn quarantine_link(
+    db: &Pool<MySql>,
+    namespace: &str,
+    id: &str,
+    actor: TokenIdentity,
+) -> Result<(), StatusCode> {
+    set_quarantine(db, namespace, id, actor, true).await
+}
+
+/// Lifts the quarantine of the link
+pub async fn release_link(
+    db: &Pool<MySql>,
+    namespace: &str,
+    id: &str,
+    actor: TokenIdentity,
+) -> Result<(), StatusCode> {
+    set_quarantine(db, namespace, id, actor, false).await
+}
+
+#[tracing::instrument(skip_all)]
+async fn set_quarantine(
+    db: &Pool<MySql>,
+    namespace: &str,
+    id: &str,
+    actor: TokenIdentity,
+    quarantined: bool,
+) -> Result<(), StatusCode> {
+    if !actor.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut tx = begin(db).await?;
+
+    lock_managed_link(&mut tx, namespace, id, &actor).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE links
+        SET quarantined_at = IF(?, COALESCE(quarantined_at, CURRENT_TIMESTAMP), NULL)
+        WHERE domain = ? AND id = ?
+        "#,
+        quarantined,
+        namespace,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error changing quarantine of link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    commit(tx).await
+}
+
+/// Removes the link with its origin and revisions, leaving a tombstone so that the id is never
+/// reused
+#[tracing::instrument(skip_all)]
+pub async fn delete_link(
+    db: &Pool<MySql>,
+    namespace: &str,
+    id: &str,
+    actor: TokenIdentity,
+) -> Result<(), StatusCode> {
+    let mut tx = begin(db).await?;
+
+    lock_managed_link(&mut tx, namespace, id, &actor).await?;
+
+    sqlx::query!(
+        "DELETE FROM links WHERE domain = ? AND id = ?",
+        namespace,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error deleting link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "DELETE FROM origins WHERE domain = ? AND id = ?",
+        namespace,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error deleting origin of link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "DELETE FROM link_revisions WHERE link_domain = ? AND link_id = ?",
+        namespace,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error deleting revisions of link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "INSERT INTO link_tombstones (domain, id, actor_token) values (?, ?, ?)",
+        namespace,
+        id,
+        actor.id()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        log::error!("Error recording tombstone of link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    commit(tx).await
+}
@@ -0,0 +1,384 @@
+use std::{net::IpAddr, str::FromStr};
+
+use axum::http::{StatusCode, Uri};
+use chrono::{Duration, Utc};
+use rand::prelude::*;
+use sqlx::{MySql, MySqlConnection, MySqlExecutor, Pool};
+
+use crate::{
+    base58::Base58Chars,
+    json_schemas::create_link_params::CreateLinkParams,
+    responses::link_creation_error::LinkCreationError,
+    service_config::{domains::DomainConfig, ServiceConfig},
+    strikes::get_strikes,
+    tokens::{identify_token, TokenIdentity},
+};
+
+pub mod cache;
+pub mod domain;
+pub mod management;
+pub mod password;
+pub mod qr;
+pub mod redirect;
+
+/// Checks that the supplied token (if any) is allowed to create links on the domain, if link
+/// creation requires authentication. Returns who the supplied token belongs to.
+#[tracing::instrument(skip_all)]
+pub async fn check_creation_auth(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    domain: &DomainConfig,
+    token: Option<&str>,
+) -> Result<Option<TokenIdentity>, StatusCode> {
+    let Some(tok_config) = &config.token_config else {
+        return Ok(None);
+    };
+    let creation_requires_auth = domain
+        .creation_requires_auth
+        .unwrap_or(tok_config.creation_requires_auth);
+    let Some(token) = token else {
+        return if creation_requires_auth {
+            Err(StatusCode::UNAUTHORIZED)
+        } else {
+            Ok(None)
+        };
+    };
+    let identity = identify_token(db, &tok_config.master_tokens, token).await;
+    if !creation_requires_auth {
+        // A stale or unknown token doesn't stop anyone from creating links anonymously
+        return match identity {
+            Ok(identity) => Ok(Some(identity)),
+            Err(StatusCode::UNAUTHORIZED) => Ok(None),
+            Err(status) => Err(status),
+        };
+    }
+    let identity = identity?;
+    if !identity.can_create_links() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(Some(identity))
+}
+
+/// Checks that the requested link options can be used by the creator
+pub fn check_link_params(
+    params: &CreateLinkParams,
+    creator: Option<&TokenIdentity>,
+) -> Result<(), StatusCode> {
+    // Anonymous editable links could only ever be edited by admins
+    if params.editable && creator.is_none() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    if params
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+/// Rejects the client if IP recording is enabled and it's banned for too many strikes
+#[tracing::instrument(skip_all)]
+pub async fn check_strikes(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    ip: IpAddr,
+) -> Result<(), LinkCreationError> {
+    if config.ip_recording.is_none() {
+        return Ok(());
+    }
+    let strikes = get_strikes(db, config, ip).await?;
+    if strikes.banned {
+        return Err(LinkCreationError::Banned {
+            until: strikes.banned_until,
+        });
+    }
+    Ok(())
+}
+
+/// Parses a link submitted for shortening
+pub fn parse_link(url: &str) -> Option<Uri> {
+    Uri::from_str(url.trim()).ok()
+}
+
+#[derive(Debug)]
+struct ExistingLinkQuery {
+    id: String,
+}
+
+/// Returns the id of an existing link on the domain with the same hash and redirect options or
+/// creates a new one. `created_by` is the serialized origin IP, only recorded if provided.
+/// `owner` is the id of the token that created the link; existing links keep their original
+/// owner. Editable and password-protected links are never deduplicated, so that changing the
+/// destination or password doesn't affect other users.
+#[tracing::instrument(skip_all)]
+pub async fn create_link(
+    conn: &mut MySqlConnection,
+    domain: &DomainConfig,
+    uri: &Uri,
+    created_by: Option<&[u8]>,
+    owner: Option<u64>,
+    params: &CreateLinkParams,
+) -> Result<String, StatusCode> {
+    let namespace = domain.namespace();
+    let uri_hash = blake3::hash(uri.to_string().as_ref());
+    let uri_hash_bytes: [u8; 32] = uri_hash.into();
+    let redirect_code = params.redirect_code.map(u16::from);
+
+    if !params.editable
+        && params.expires_at.is_none()
+        && let Some(ExistingLinkQuery { id }) = sqlx::query_as!(
+            ExistingLinkQuery,
+            r#"
+            SELECT id
+            FROM links
+            WHERE domain = ?
+            AND hash = ?
+            AND editable = FALSE
+            AND password_hash IS NULL
+            AND quarantined_at IS NULL
+            AND redirect_code <=> ?
+            AND forward_query = ?
+            AND forward_path = ?
+            AND cache_max_age <=> ?
+            AND expires_at IS NULL
+            "#,
+            namespace,
+            uri_hash_bytes.as_ref(),
+            redirect_code,
+            params.forward_query,
+            params.forward_path,
+            params.cache_max_age
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| {
+            log::error!("Error when looking for existing link: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+    {
+        return Ok(id);
+    }
+
+    let new_link_id = generate_link_id(&mut *conn, domain).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO links (
+            domain,
+            id,
+            hash,
+            link,
+            created_by_token,
+            editable,
+            password_hash,
+            redirect_code,
+            forward_query,
+            forward_path,
+            cache_max_age,
+            expires_at
+        ) values (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        namespace,
+        &new_link_id,
+        uri_hash_bytes.as_ref(),
+        uri.to_string(),
+        owner,
+        params.editable,
+        params.password_hash,
+        redirect_code,
+        params.forward_query,
+        params.forward_path,
+        params.cache_max_age,
+        params.expires_at
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        log::error!("Error when inserting new link: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if let Some(created_by) = created_by {
+        sqlx::query!(
+            "INSERT INTO origins (domain, id, created_by) values (?, ?, ?)",
+            namespace,
+            &new_link_id,
+            created_by
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            log::error!("Error when inserting link origin: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    Ok(new_link_id)
+}
+
+#[derive(Debug)]
+struct IdTakenQuery {
+    taken: bool,
+}
+
+/// Random ids tried before giving up, only a nearly full id space needs more than a few
+const MAX_ID_ATTEMPTS: usize = 10;
+
+/// Generates a random id that isn't used by any link on the domain and wasn't used by a deleted
+/// one
+#[tracing::instrument(skip_all)]
+async fn generate_link_id(
+    conn: &mut MySqlConnection,
+    domain: &DomainConfig,
+) -> Result<String, StatusCode> {
+    let namespace = domain.namespace();
+    for _ in 0..MAX_ID_ATTEMPTS {
+        let rng = StdRng::from_os_rng();
+        let id: String = rng
+            .sample_iter(Base58Chars)
+            .take(usize::from(domain.id_length))
+            .collect();
+        let IdTakenQuery { taken } = sqlx::query_as!(
+            IdTakenQuery,
+            r#"
+            SELECT (
+                EXISTS (SELECT * FROM links WHERE domain = ? AND id = ?)
+                OR EXISTS (SELECT * FROM link_tombstones WHERE domain = ? AND id = ?)
+            ) AS `taken: _`
+            "#,
+            namespace,
+            &id,
+            namespace,
+            &id
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| {
+            log::error!("Error checking if link id `{id}` is taken: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        if !taken {
+            return Ok(id);
+        }
+    }
+    log::error!(
+        "No free link id found on `{namespace}` in {MAX_ID_ATTEMPTS} attempts, increase `id_length`"
+    );
+    Err(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[derive(Debug)]
+struct TombstoneQuery {
+    deleted: bool,
+}
+
+/// Whether a link with this id existed in the namespace and was deleted
+#[tracing::instrument(skip_all)]
+pub async fn is_deleted<'c>(
+    executor: impl MySqlExecutor<'c>,
+    namespace: &str,
+    id: &str,
+) -> Result<bool, StatusCode> {
+    Ok(sqlx::query_as!(
+        TombstoneQuery,
+        "SELECT EXISTS (SELECT * FROM link_tombstones WHERE domain = ? AND id = ?) AS `deleted: _`",
+        namespace,
+        id
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(|e| {
+        log::error!("Error looking up tombstone of link `{id}`: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .deleted)
+}
+
+/// Serialized origin IP to record with new links, if IP recording is enabled
+pub fn link_origin(config: &ServiceConfig, ip: IpAddr) -> Result<Option<Vec<u8>>, StatusCode> {
+    if config.ip_recording.is_none() {
+        return Ok(None);
+    }
+    bincode::serialize(&ip)
+        .map(Some)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Removes recorded origins older than the retention period. Returns how many were removed.
+#[tracing::instrument(skip_all)]
+pub async fn purge_expired_origins(
+    db: &Pool<MySql>,
+    retention_period: Duration,
+) -> Result<u64, sqlx::Error> {
+    // Nothing is older than a period reaching before the earliest date
+    let Some(expired_date) = Utc::now().checked_sub_signed(retention_period) else {
+        return Ok(0);
+    };
+    Ok(
+        sqlx::query!("DELETE FROM origins WHERE created_at < ?", expired_date)
+            .execute(db)
+            .await?
+            .rows_affected(),
+    )
+}
+
+/// Removes recorded origins of links that don't exist anymore. Returns how many were removed.
+#[tracing::instrument(skip_all)]
+pub async fn purge_orphaned_origins(db: &Pool<MySql>) -> Result<u64, sqlx::Error> {
+    Ok(sqlx::query(
+        r#"
+        DELETE FROM origins orgs
+        WHERE NOT EXISTS (
+            SELECT * FROM links linkst
+            WHERE linkst.domain = orgs.domain AND linkst.id = orgs.id
+        )
+        "#,
+    )
+    .execute(db)
+    .await?
+    .rows_affected())
+}
+
+/// Deletes links past their expiration time with their origins and revisions, leaving tombstones
+/// so that the ids are never reused. Returns how many links were deleted.
+#[tracing::instrument(skip_all)]
+pub async fn purge_expired_links(db: &Pool<MySql>) -> Result<u64, sqlx::Error> {
+    let now = Utc::now();
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO link_tombstones (domain, id)
+        SELECT domain, id FROM links WHERE expires_at <= ?
+        "#,
+    )
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        DELETE FROM origins
+        WHERE (domain, id) in (SELECT domain, id FROM links WHERE expires_at <= ?)
+        "#,
+    )
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        DELETE FROM link_revisions
+        WHERE (link_domain, link_id) in (SELECT domain, id FROM links WHERE expires_at <= ?)
+        "#,
+    )
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    let deleted = sqlx::query!("DELETE FROM links WHERE expires_at <= ?", now)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+    Ok(deleted)
+}
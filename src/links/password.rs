@@ -0,0 +1,87 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::http::StatusCode;
+
+use crate::service_config::link_passwords::LinkPasswordsConfig;
+
+/// Hashes a link password with argon2. Hashing is slow on purpose, so it's done off the async
+/// runtime.
+pub async fn hash_password(password: String) -> Result<String, StatusCode> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+    })
+    .await
+    .map_err(|e| {
+        log::error!("Password hashing task failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .map_err(|e| {
+        log::error!("Error hashing link password: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+pub async fn verify_password(password: String, hash: String) -> Result<bool, StatusCode> {
+    tokio::task::spawn_blocking(move || {
+        let hash = PasswordHash::new(&hash)?;
+        match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(()) => Ok(true),
+            Err(argon2::password_hash::Error::Password) => Ok(false),
+            Err(e) => Err(e),
+        }
+    })
+    .await
+    .map_err(|e| {
+        log::error!("Password verification task failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .map_err(|e| {
+        log::error!("Error verifying link password: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Failed password attempts per client IP, used to rate-limit password guessing
+#[derive(Debug, Default)]
+pub struct FailedAttempts {
+    attempts: Mutex<HashMap<IpAddr, (u32, Instant)>>,
+}
+
+impl FailedAttempts {
+    /// Whether the client has used up its failed attempts for the lockout period
+    pub fn is_locked_out(&self, ip: IpAddr, config: &LinkPasswordsConfig) -> bool {
+        let period = lockout_period(config);
+        let attempts = self.attempts.lock().unwrap();
+        attempts.get(&ip).is_some_and(|(count, first_failure)| {
+            *count >= config.max_failed_attempts && first_failure.elapsed() < period
+        })
+    }
+
+    pub fn record_failure(&self, ip: IpAddr, config: &LinkPasswordsConfig) {
+        let period = lockout_period(config);
+        let mut attempts = self.attempts.lock().unwrap();
+        attempts.retain(|_, (_, first_failure)| first_failure.elapsed() < period);
+        let (count, _) = attempts.entry(ip).or_insert((0, Instant::now()));
+        *count += 1;
+    }
+
+    pub fn clear(&self, ip: IpAddr) {
+        self.attempts.lock().unwrap().remove(&ip);
+    }
+}
+
+fn lockout_period(config: &LinkPasswordsConfig) -> Duration {
+    config.lockout_period.to_std().unwrap_or_default()
+}
@@ -0,0 +1,115 @@
+use std::fmt::Write;
+
+use axum::http::StatusCode;
+use qrcode::{Color, EcLevel, QrCode};
+
+use crate::json_schemas::qr_params::{QrErrorCorrection, QrFormat, QrParams};
+
+pub const MIN_SIZE: u32 = 32;
+pub const MAX_SIZE: u32 = 1024;
+pub const MAX_MARGIN: u32 = 32;
+
+/// Modules of a QR code, dark or light, row by row
+struct Modules {
+    width: u32,
+    dark: Vec<bool>,
+}
+
+impl Modules {
+    fn new(data: &str, params: &QrParams) -> Result<Self, StatusCode> {
+        let ec_level = match params.ecc {
+            QrErrorCorrection::L => EcLevel::L,
+            QrErrorCorrection::M => EcLevel::M,
+            QrErrorCorrection::Q => EcLevel::Q,
+            QrErrorCorrection::H => EcLevel::H,
+        };
+        let code = QrCode::with_error_correction_level(data, ec_level).map_err(|e| {
+            log::error!("Error encoding `{data}` as QR code: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        Ok(Self {
+            width: code.width() as u32,
+            dark: code
+                .to_colors()
+                .into_iter()
+                .map(|color| color == Color::Dark)
+                .collect(),
+        })
+    }
+
+    fn is_dark(&self, x: u32, y: u32) -> bool {
+        self.dark[(y * self.width + x) as usize]
+    }
+}
+
+/// Renders the data as QR code image in the format. Returns 400 if the options are out of range.
+pub fn render_qr(data: &str, format: QrFormat, params: &QrParams) -> Result<Vec<u8>, StatusCode> {
+    if !(MIN_SIZE..=MAX_SIZE).contains(&params.size) || params.margin > MAX_MARGIN {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let modules = Modules::new(data, params)?;
+    match format {
+        QrFormat::Svg => Ok(render_svg(&modules, params).into_bytes()),
+        QrFormat::Png => render_png(&modules, params),
+    }
+}
+
+/// SVG drawn in modules and scaled to the size, so it's exactly the requested size
+fn render_svg(modules: &Modules, params: &QrParams) -> String {
+    let total = modules.width + 2 * params.margin;
+    let mut path = String::new();
+    for y in 0..modules.width {
+        for x in 0..modules.width {
+            if modules.is_dark(x, y) {
+                write!(path, "M{},{}h1v1h-1z", x + params.margin, y + params.margin).unwrap();
+            }
+        }
+    }
+    format!(
+        concat!(
+            r#"<?xml version="1.0" standalone="yes"?>"#,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{size}" height="{size}" "#,
+            r#"viewBox="0 0 {total} {total}" shape-rendering="crispEdges">"#,
+            r#"<rect width="{total}" height="{total}" fill="{bg}"/>"#,
+            r#"<path fill="{fg}" d="{path}"/></svg>"#
+        ),
+        size = params.size,
+        total = total,
+        bg = params.bg,
+        fg = params.fg,
+        path = path
+    )
+}
+
+/// PNG with a whole number of pixels per module, so it can be smaller than the requested size
+fn render_png(modules: &Modules, params: &QrParams) -> Result<Vec<u8>, StatusCode> {
+    let total = modules.width + 2 * params.margin;
+    let scale = (params.size / total).max(1);
+    let pixels = total * scale;
+
+    let mut image = Vec::with_capacity((pixels * pixels * 3) as usize);
+    for py in 0..pixels {
+        for px in 0..pixels {
+            let (x, y) = (px / scale, py / scale);
+            let dark = (params.margin..params.margin + modules.width).contains(&x)
+                && (params.margin..params.margin + modules.width).contains(&y)
+                && modules.is_dark(x - params.margin, y - params.margin);
+            let color = if dark { params.fg } else { params.bg };
+            image.extend_from_slice(&color.0);
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, pixels, pixels);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&image))
+        .map_err(|e| {
+            log::error!("Error encoding QR code as PNG: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(png)
+}
@@ -0,0 +1,97 @@
+use axum::http::StatusCode;
+
+use crate::{
+    json_schemas::redirect_code::RedirectCode, responses::short_link_redirect::ShortLinkRedirect,
+    service_config::redirects::RedirectsConfig,
+};
+
+/// Redirect behaviour stored with a link
+#[derive(Debug, Clone, Default)]
+pub struct RedirectOptions {
+    pub redirect_code: Option<RedirectCode>,
+    pub forward_query: bool,
+    pub forward_path: bool,
+    pub cache_max_age: Option<u32>,
+}
+
+/// Builds the redirect to the link destination. `path` is the raw path requested after the link
+/// id and `query` is the raw query string of the request.
+pub fn redirect_to(
+    link: &str,
+    options: &RedirectOptions,
+    config: &RedirectsConfig,
+    path: Option<&str>,
+    query: Option<&str>,
+) -> Result<ShortLinkRedirect, StatusCode> {
+    let path = path.filter(|path| !path.is_empty());
+    if path.is_some() && !options.forward_path {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let query = query.filter(|_| options.forward_query);
+
+    let code = options.redirect_code.unwrap_or(config.default_code);
+    let cache_max_age = code.is_permanent().then(|| {
+        options.cache_max_age.unwrap_or_else(|| {
+            u32::try_from(config.permanent_cache_max_age.num_seconds()).unwrap_or(u32::MAX)
+        })
+    });
+
+    Ok(ShortLinkRedirect {
+        location: build_location(link, path, query),
+        status: code.status(),
+        cache_max_age,
+    })
+}
+
+/// Redirect after a submitted form, such as the password form of a protected link. It always
+/// uses 303 so that the client follows it with a GET request.
+pub fn redirect_after_form(
+    link: &str,
+    options: &RedirectOptions,
+    path: Option<&str>,
+    query: Option<&str>,
+) -> Result<ShortLinkRedirect, StatusCode> {
+    let config = RedirectsConfig {
+        default_code: RedirectCode::SEE_OTHER,
+        ..Default::default()
+    };
+    let options = RedirectOptions {
+        redirect_code: None,
+        ..options.clone()
+    };
+    redirect_to(link, &options, &config, path, query)
+}
+
+/// Appends the path and query to the destination, keeping its own query and fragment in place
+fn build_location(link: &str, path: Option<&str>, query: Option<&str>) -> String {
+    let (rest, fragment) = match link.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (link, None),
+    };
+    let (base, link_query) = match rest.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (rest, None),
+    };
+
+    let mut location = base.to_string();
+    if let Some(path) = path {
+        if !location.ends_with('/') {
+            location.push('/');
+        }
+        location.push_str(path.trim_start_matches('/'));
+    }
+    let queries: Vec<&str> = [link_query, query]
+        .into_iter()
+        .flatten()
+        .filter(|query| !query.is_empty())
+        .collect();
+    if !queries.is_empty() {
+        location.push('?');
+        location.push_str(&queries.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        location.push('#');
+        location.push_str(fragment);
+    }
+    location
+}
@@ -0,0 +1,61 @@
+use std::{
+    error::Error,
+    io::{self, IsTerminal},
+    sync::OnceLock,
+};
+
+use tracing_subscriber::{
+    filter::LevelFilter, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, Registry,
+};
+
+use crate::service_config::logging::{LogFormat, LoggingConfig};
+
+/// Level filter of the logger, set once it's initialized
+static LEVEL: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+/// Sets up logging of the server. Records of the `log` crate become events of the current span,
+/// so every line logged while handling a request carries its id.
+pub fn init(
+    config: &LoggingConfig,
+    level: log::LevelFilter,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (filter, handle) = reload::Layer::new(tracing_level(level));
+    let registry = tracing_subscriber::registry().with(filter);
+    let ansi = io::stdout().is_terminal();
+    match config.format {
+        LogFormat::Text => registry.with(fmt::layer().with_ansi(ansi)).try_init()?,
+        LogFormat::Json => registry
+            .with(
+                fmt::layer()
+                    .json()
+                    .with_current_span(false)
+                    .with_span_list(true),
+            )
+            .try_init()?,
+    }
+    // Only fails if it's already set, in which case the logger wasn't initialized again either
+    let _ = LEVEL.set(handle);
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Changes the log level of the running service
+pub fn set_level(level: log::LevelFilter) {
+    if let Some(handle) = LEVEL.get()
+        && let Err(why) = handle.reload(tracing_level(level))
+    {
+        log::error!("Failed to change the log level: {why}");
+    }
+    log::set_max_level(level);
+}
+
+fn tracing_level(level: log::LevelFilter) -> LevelFilter {
+    match level {
+        log::LevelFilter::Off => LevelFilter::OFF,
+        log::LevelFilter::Error => LevelFilter::ERROR,
+        log::LevelFilter::Warn => LevelFilter::WARN,
+        log::LevelFilter::Info => LevelFilter::INFO,
+        log::LevelFilter::Debug => LevelFilter::DEBUG,
+        log::LevelFilter::Trace => LevelFilter::TRACE,
+    }
+}
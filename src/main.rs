@@ -1,120 +1,142 @@
-use std::{error::Error, future::IntoFuture, net::SocketAddr, sync::Arc};
-
-use chrono::Utc;
-use service_config::ServiceConfig;
-use simple_logger::SimpleLogger;
-use sqlx::{MySql, Pool};
-use tokio_cron_scheduler::{Job, JobScheduler};
-
-use crate::{database::connect_db, routes::create_router};
-
-mod base58;
-mod custom_headers;
-mod database;
-mod json_schemas;
-mod responses;
-mod routes;
-mod service_config;
-mod tokens;
-
-pub type DbPool = Arc<Pool<MySql>>;
-
-#[derive(Clone)]
-pub struct ServiceState {
-    pub db: DbPool,
-    pub config: ServiceConfig,
-}
+use std::{error::Error, future::IntoFuture, net::SocketAddr, process, sync::Arc};
+
+use axum::{middleware, serve::ListenerExt};
+use sayless::{
+    database::connect_db,
+    links::cache::LinkCache,
+    logging,
+    maintenance::schedule_jobs,
+    routes::{create_router, request_context::request_context},
+    server,
+    service_config::{
+        self,
+        reload::{watch_config, SharedConfig},
+    },
+    ServiceState, MIGRATOR,
+};
+use tokio::task::JoinSet;
+use tokio_cron_scheduler::JobScheduler;
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
-    let config = service_config::get_config()
-        .await
-        .expect("Reading config failed");
-
-    let ip_record_config = config.ip_recording.clone();
-
-    if let Some(log_level) = config.log_level {
-        simple_logger::init_with_level(log_level)?;
-    } else {
-        SimpleLogger::new()
-            .with_level(log::LevelFilter::Info)
-            .env()
-            .init()?;
+    if std::env::args().skip(1).any(|arg| arg == "--check-config") {
+        check_config().await;
     }
 
+    let config = match service_config::get_config().await {
+        Ok(config) => config,
+        Err(why) => {
+            eprintln!("Reading config failed: {why}");
+            process::exit(1);
+        }
+    };
+
+    logging::init(&config.logging, config.log_level_filter())?;
+
+    match &config.sources.file {
+        Some(path) => log::info!("Loaded config from {}", path.display()),
+        None => log::warn!(
+            "No config file found, running on defaults and `SAYLESS_CONFIG__*` variables. Set CONFIG_FILE or create `config.toml` if that's not intended."
+        ),
+    }
     log::debug!("Configuration: {config:?}");
+    log::debug!("Configuration sources:\n{}", config.sources);
+    if let Some(tok_config) = &config.token_config {
+        for weakness in tok_config.master_tokens.weaknesses() {
+            log::warn!("{weakness}");
+        }
+    }
 
     if let Err(why) = dotenvy::dotenv() {
         log::warn!("Failed to load environment variables from `.env`: {why}");
         log::info!("If you're not using `.env` file for setting environment variables, you can safely ignore this message.");
     }
 
-    let server_port: u16 = dotenvy::var("PORT")
-        .expect("PORT environment variable must be set")
-        .parse()
-        .expect("Parsing port number failed");
+    let listeners = match server::bind(&config.server).await {
+        Ok(listeners) => listeners,
+        Err(why) => {
+            log::error!("{why}");
+            process::exit(1);
+        }
+    };
 
     let db = Arc::new(connect_db().await?);
 
     log::info!("Applying migrations");
-    sqlx::migrate!().run(db.as_ref()).await?;
+    MIGRATOR.run(db.as_ref()).await?;
 
     let router = create_router(&config);
 
+    let link_cache = Arc::new(LinkCache::new(&config.redirect_cache));
+    let config = SharedConfig::new(config);
+    tokio::spawn(watch_config(config.clone()));
+
     let state = ServiceState {
         db: Arc::clone(&db),
-        config,
+        link_cache,
+        config: config.clone(),
+        password_attempts: Arc::default(),
+        maintenance: Arc::default(),
     };
 
-    let router = router.with_state(state);
-
-    log::info!("Starting server");
-    let server_handle = tokio::spawn(
-        axum::serve(
-            tokio::net::TcpListener::bind(format!("0.0.0.0:{server_port}"))
-                .await
-                .unwrap(),
-            router.into_make_service_with_connect_info::<SocketAddr>(),
-        )
-        .into_future(),
-    );
+    let router = router
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            request_context,
+        ))
+        .with_state(state.clone());
+
+    let mut servers = JoinSet::new();
+    for (address, listener) in listeners {
+        log::info!("Starting server on {address}");
+        // `tap_io` makes the client address of the listener available as `ConnectInfo`
+        servers.spawn(
+            axum::serve(
+                listener.tap_io(|_| {}),
+                router
+                    .clone()
+                    .into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .into_future(),
+        );
+    }
+
+    let sched = JobScheduler::new().await?;
+    schedule_jobs(&sched, &state).await?;
+
+    // Not shut down on Ctrl-C, listening for it would keep the signal from stopping the server
+    sched.start().await?;
 
-    if let Some(ip_recoding_config) = ip_record_config {
-        let sched = JobScheduler::new().await?;
-
-        sched
-            .add(Job::new_async(
-                ip_recoding_config.retention_check_period.as_ref(),
-                move |_, _| {
-                    let db_cloned = Arc::clone(&db);
-                    Box::pin(async move {
-                        log::debug!("IP address retention check");
-                        let expired_date = Utc::now() - ip_recoding_config.retention_period;
-                        if let Err(why) = sqlx::query(
-                            r#"
-                            DELETE FROM origins orgs
-                            WHERE orgs.id in (
-                                SELECT linkst.id
-                                FROM links linkst
-                                WHERE created_at < ?
-                            )"#,
-                        )
-                        .bind(expired_date)
-                        .execute(db_cloned.as_ref())
-                        .await
-                        {
-                            log::error!("Error in IP retention check query: {}", why);
-                        }
-                    })
-                },
-            )?)
-            .await?;
-
-        sched.shutdown_on_ctrl_c();
-
-        sched.start().await?;
+    while let Some(server) = servers.join_next().await {
+        server??;
     }
-    server_handle.await??;
 
     Ok(())
 }
+
+/// Validates the config file without starting the server, printing every problem found. Exits
+/// with 1 if there are any.
+async fn check_config() -> ! {
+    let path = service_config::config_path().map_or_else(
+        || "environment".to_owned(),
+        |path| path.display().to_string(),
+    );
+    match service_config::get_config().await {
+        Ok(config) => {
+            if let Some(tok_config) = &config.token_config {
+                for weakness in tok_config.master_tokens.weaknesses() {
+                    eprintln!("{path}: warning: {weakness}");
+                }
+            }
+            if config.sources.file.is_none() {
+                eprintln!("{path}: warning: no config file found, only defaults and `SAYLESS_CONFIG__*` variables are used");
+            }
+            println!("{path}: config is valid");
+            process::exit(0);
+        }
+        Err(why) => {
+            eprintln!("{path}: {why}");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{MySql, Pool};
+use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError};
+
+use crate::{
+    audit::{purge_expired_client_ips, purge_expired_events},
+    links::{purge_expired_links, purge_expired_origins, purge_orphaned_origins},
+    responses::maintenance::{MaintenanceJobRun, MaintenanceJobStatus},
+    service_config::ServiceConfig,
+    strikes::decay_strikes,
+    tokens::purge_expired_tokens,
+    ServiceState,
+};
+
+/// Database cleanups that run on cron schedules, or on demand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceJob {
+    /// Removes recorded IPs of links and client IPs of audit events older than `retention_period`
+    /// of `[ip_recording]`
+    IpRetention,
+    /// Removes audit events older than `retention_period` of `[audit_log]`
+    AuditLogRetention,
+    /// Deletes expired and revoked tokens
+    ExpiredTokens,
+    /// Deletes links past their expiration time
+    ExpiredLinks,
+    /// Takes one strike from every IP
+    StrikeDecay,
+    /// Removes recorded IPs of links that don't exist anymore
+    OrphanedOrigins,
+}
+
+impl MaintenanceJob {
+    pub const ALL: [Self; 6] = [
+        Self::IpRetention,
+        Self::AuditLogRetention,
+        Self::ExpiredTokens,
+        Self::ExpiredLinks,
+        Self::StrikeDecay,
+        Self::OrphanedOrigins,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::IpRetention => "ip_retention",
+            Self::AuditLogRetention => "audit_log_retention",
+            Self::ExpiredTokens => "expired_tokens",
+            Self::ExpiredLinks => "expired_links",
+            Self::StrikeDecay => "strike_decay",
+            Self::OrphanedOrigins => "orphaned_origins",
+        }
+    }
+
+    /// IP retention only exists with IP recording, the other jobs always do
+    pub fn is_available(self, config: &ServiceConfig) -> bool {
+        self != Self::IpRetention || config.ip_recording.is_some()
+    }
+
+    /// Cron schedule of the job, `None` if it only runs when triggered
+    pub fn schedule(self, config: &ServiceConfig) -> Option<Arc<str>> {
+        match self {
+            Self::IpRetention => config
+                .ip_recording
+                .as_ref()
+                .map(|ip_recording| ip_recording.retention_check_period.clone()),
+            Self::AuditLogRetention => Some(config.audit_log.retention_check_period.clone()),
+            Self::ExpiredTokens => config.maintenance.expired_tokens.clone(),
+            Self::ExpiredLinks => config.maintenance.expired_links.clone(),
+            Self::StrikeDecay => config.maintenance.strike_decay.clone(),
+            Self::OrphanedOrigins => config.maintenance.orphaned_origins.clone(),
+        }
+    }
+
+    /// Runs the job once. Returns the amount of affected rows.
+    pub async fn execute(
+        self,
+        db: &Pool<MySql>,
+        config: &ServiceConfig,
+    ) -> Result<u64, sqlx::Error> {
+        match self {
+            Self::IpRetention => match &config.ip_recording {
+                Some(ip_recording) => Ok(purge_expired_origins(db, ip_recording.retention_period)
+                    .await?
+                    + purge_expired_client_ips(db, ip_recording.retention_period).await?),
+                None => Ok(0),
+            },
+            Self::AuditLogRetention => {
+                purge_expired_events(db, config.audit_log.retention_period).await
+            }
+            Self::ExpiredTokens => purge_expired_tokens(db).await,
+            Self::ExpiredLinks => purge_expired_links(db).await,
+            Self::StrikeDecay => decay_strikes(db, &config.strikes).await,
+            Self::OrphanedOrigins => purge_orphaned_origins(db).await,
+        }
+    }
+}
+
+impl fmt::Display for MaintenanceJob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MaintenanceJob {
+    type Err = String;
+
+    fn from_str(job: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|known| known.as_str() == job)
+            .ok_or_else(|| format!("Unknown maintenance job `{job}`"))
+    }
+}
+
+#[derive(Debug, Default)]
+struct JobState {
+    running: bool,
+    runs: u64,
+    failures: u64,
+    last_run: Option<MaintenanceJobRun>,
+}
+
+/// Runs of the maintenance jobs since startup
+#[derive(Debug, Default)]
+pub struct Maintenance {
+    jobs: Mutex<HashMap<MaintenanceJob, JobState>>,
+}
+
+impl Maintenance {
+    /// Status of the jobs available with the config
+    pub fn status(&self, config: &ServiceConfig) -> Vec<MaintenanceJobStatus> {
+        let jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        MaintenanceJob::ALL
+            .into_iter()
+            .filter(|job| job.is_available(config))
+            .map(|job| {
+                let state = jobs.get(&job);
+                MaintenanceJobStatus {
+                    job,
+                    schedule: job.schedule(config).map(|schedule| schedule.to_string()),
+                    running: state.is_some_and(|state| state.running),
+                    runs: state.map_or(0, |state| state.runs),
+                    failures: state.map_or(0, |state| state.failures),
+                    last_run: state.and_then(|state| state.last_run.clone()),
+                }
+            })
+            .collect()
+    }
+
+    /// Marks the job as running. Returns `false` if it already is.
+    fn start(&self, job: MaintenanceJob) -> bool {
+        let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = jobs.entry(job).or_default();
+        !std::mem::replace(&mut state.running, true)
+    }
+
+    fn finish(&self, job: MaintenanceJob, run: MaintenanceJobRun) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = jobs.entry(job).or_default();
+        state.running = false;
+        state.runs += 1;
+        if run.error.is_some() {
+            state.failures += 1;
+        }
+        state.last_run = Some(run);
+    }
+}
+
+/// Runs the job and records the run. Returns `None` without running it if it's already running.
+/// The job runs in its own task, so it finishes even if the request that triggered it doesn't.
+pub async fn run_job(
+    state: &ServiceState,
+    job: MaintenanceJob,
+    triggered: bool,
+) -> Option<MaintenanceJobRun> {
+    if !state.maintenance.start(job) {
+        return None;
+    }
+    let state = state.clone();
+    tokio::spawn(async move {
+        let started_at = Utc::now();
+        let start = Instant::now();
+        let config = state.config.load();
+        let result = job.execute(state.db.as_ref(), &config).await;
+        let duration_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+        let run = match result {
+            Ok(rows_affected) => {
+                log::info!(
+                    "Maintenance job `{job}` affected {rows_affected} rows in {duration_ms} ms"
+                );
+                MaintenanceJobRun {
+                    started_at,
+                    duration_ms,
+                    triggered,
+                    rows_affected: Some(rows_affected),
+                    error: None,
+                }
+            }
+            Err(why) => {
+                log::error!("Maintenance job `{job}` failed: {why}");
+                MaintenanceJobRun {
+                    started_at,
+                    duration_ms,
+                    triggered,
+                    rows_affected: None,
+                    error: Some(why.to_string()),
+                }
+            }
+        };
+        state.maintenance.finish(job, run.clone());
+        run
+    })
+    .await
+    .ok()
+}
+
+/// Adds the available jobs that have a schedule to the scheduler. Schedules are only read at
+/// startup, the options of the jobs are read on every run.
+pub async fn schedule_jobs(
+    scheduler: &JobScheduler,
+    state: &ServiceState,
+) -> Result<(), JobSchedulerError> {
+    let config = state.config.load();
+    for job in MaintenanceJob::ALL {
+        if !job.is_available(&config) {
+            continue;
+        }
+        let Some(schedule) = job.schedule(&config) else {
+            continue;
+        };
+        log::debug!("Scheduling maintenance job `{job}` at `{schedule}`");
+        let state = state.clone();
+        scheduler
+            .add(Job::new_async(schedule.as_ref(), move |_, _| {
+                let state = state.clone();
+                Box::pin(async move {
+                    if run_job(&state, job, false).await.is_none() {
+                        log::warn!("Maintenance job `{job}` is still running, skipping this run");
+                    }
+                })
+            })?)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::borrow::Cow;
+
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Error of the versioned API, responded with a JSON body instead of an empty one
+#[derive(Debug, Clone, Copy)]
+pub struct ApiError(pub StatusCode);
+
+/// JSON body of versioned API errors
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiErrorBody {
+    pub status: u16,
+    pub error: Cow<'static, str>,
+    /// When the ban of the client lifts, for link creation rejected because of strikes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub banned_until: Option<DateTime<Utc>>,
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        Self(status)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            status: self.0.as_u16(),
+            error: self.0.canonical_reason().unwrap_or("Unknown error").into(),
+            banned_until: None,
+        };
+        (self.0, Json(body)).into_response()
+    }
+}
+
+/// Rejected request body, path or query of the versioned API, with the reason in the JSON body
+#[derive(Debug)]
+pub struct ApiRejection {
+    status: StatusCode,
+    error: String,
+}
+
+impl IntoResponse for ApiRejection {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            status: self.status.as_u16(),
+            error: self.error.into(),
+            banned_until: None,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiRejection {
+    fn from(rejection: JsonRejection) -> Self {
+        Self {
+            status: rejection.status(),
+            error: rejection.body_text(),
+        }
+    }
+}
+
+impl From<PathRejection> for ApiRejection {
+    fn from(rejection: PathRejection) -> Self {
+        Self {
+            status: rejection.status(),
+            error: rejection.body_text(),
+        }
+    }
+}
+
+impl From<QueryRejection> for ApiRejection {
+    fn from(rejection: QueryRejection) -> Self {
+        Self {
+            status: rejection.status(),
+            error: rejection.body_text(),
+        }
+    }
+}
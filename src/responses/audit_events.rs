@@ -0,0 +1,29 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::link_info::RevisionActor;
+use crate::audit::AuditAction;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEventList {
+    pub events: Vec<AuditEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub id: u64,
+    pub created_at: DateTime<Utc>,
+    pub actor: RevisionActor,
+    pub action: AuditAction,
+    pub target: String,
+    /// Namespace of link targets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    /// Only recorded with IP recording, and only shown to tokens that can view IPs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<IpAddr>,
+}
@@ -0,0 +1,28 @@
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkLinkResult {
+    pub link: String,
+    #[serde(flatten)]
+    pub outcome: BulkLinkOutcome,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkLinkOutcome {
+    Id(String),
+    Error(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct BulkCreatedLinks(pub Vec<BulkLinkResult>);
+
+impl IntoResponse for BulkCreatedLinks {
+    fn into_response(self) -> Response {
+        Json(self.0).into_response()
+    }
+}
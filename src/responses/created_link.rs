@@ -1,19 +1,37 @@
 use axum::{
-    http::StatusCode,
+    http::{header::LOCATION, StatusCode},
     response::{IntoResponse, Response},
+    Json,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreatedLink {
     pub id: String,
+    pub location: String,
 }
 
 impl IntoResponse for CreatedLink {
     fn into_response(self) -> Response {
         Response::builder()
             .status(StatusCode::CREATED)
-            .header(axum::http::header::LOCATION, format!("/l/{}", self.id))
+            .header(LOCATION, self.location)
             .body(Default::default())
             .unwrap()
     }
 }
+
+/// Created link with the id and location also in a JSON body
+#[derive(Debug, Clone)]
+pub struct CreatedLinkJson(pub CreatedLink);
+
+impl IntoResponse for CreatedLinkJson {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::CREATED,
+            [(LOCATION, self.0.location.clone())],
+            Json(self.0),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,93 @@
+use std::time::SystemTime;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use headers::{HeaderMapExt, RetryAfter};
+
+use super::api_error::ApiErrorBody;
+
+/// Rejection of link creation. Bans are responded with 403, with the time they lift as
+/// `Retry-After` header.
+#[derive(Debug, Clone, Copy)]
+pub enum LinkCreationError {
+    Status(StatusCode),
+    /// The client IP has too many strikes, `until` is `None` if the ban doesn't lift on its own
+    Banned {
+        until: Option<DateTime<Utc>>,
+    },
+}
+
+impl LinkCreationError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Status(status) => *status,
+            Self::Banned { .. } => StatusCode::FORBIDDEN,
+        }
+    }
+
+    fn ban_message(until: Option<DateTime<Utc>>) -> String {
+        match until {
+            Some(until) => format!("Banned until {}", until.to_rfc3339()),
+            None => "Banned".to_owned(),
+        }
+    }
+
+    fn with_retry_after(&self, mut response: Response) -> Response {
+        if let Self::Banned { until: Some(until) } = self {
+            response
+                .headers_mut()
+                .typed_insert(RetryAfter::date(SystemTime::from(*until)));
+        }
+        response
+    }
+}
+
+impl From<StatusCode> for LinkCreationError {
+    fn from(status: StatusCode) -> Self {
+        Self::Status(status)
+    }
+}
+
+impl IntoResponse for LinkCreationError {
+    fn into_response(self) -> Response {
+        let response = match self {
+            Self::Status(status) => status.into_response(),
+            Self::Banned { until } => (self.status(), Self::ban_message(until)).into_response(),
+        };
+        self.with_retry_after(response)
+    }
+}
+
+/// Versioned API form of [`LinkCreationError`], with a JSON body that has `banned_until` for bans
+#[derive(Debug, Clone, Copy)]
+pub struct ApiLinkCreationError(pub LinkCreationError);
+
+impl From<LinkCreationError> for ApiLinkCreationError {
+    fn from(error: LinkCreationError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for ApiLinkCreationError {
+    fn into_response(self) -> Response {
+        let status = self.0.status();
+        let body = match self.0 {
+            LinkCreationError::Status(status) => ApiErrorBody {
+                status: status.as_u16(),
+                error: status.canonical_reason().unwrap_or("Unknown error").into(),
+                banned_until: None,
+            },
+            LinkCreationError::Banned { until } => ApiErrorBody {
+                status: status.as_u16(),
+                error: LinkCreationError::ban_message(until).into(),
+                banned_until: until,
+            },
+        };
+        self.0
+            .with_retry_after((status, Json(body)).into_response())
+    }
+}
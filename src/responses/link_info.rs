@@ -3,26 +3,79 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LinkInfo {
     pub id: String,
+    /// Removed when the link is disabled
     #[serde(with = "serde_hash")]
-    pub hash: Hash,
-    pub link: String,
+    pub hash: Option<Hash>,
+    /// Hidden for password-protected links, unless requested by the owner or an admin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
     pub created_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_by: Option<IpAddr>,
+    #[serde(default)]
+    pub editable: bool,
+    #[serde(default)]
+    pub password_protected: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled_at: Option<DateTime<Utc>>,
+    /// Set while the link is held for review by moderators, it doesn't redirect meanwhile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantined_at: Option<DateTime<Utc>>,
+    /// Only present if the link overrides the configured default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_code: Option<u16>,
+    #[serde(default)]
+    pub forward_query: bool,
+    #[serde(default)]
+    pub forward_path: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_max_age: Option<u32>,
+    /// The link stops redirecting at this time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<LinkRevision>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkRevision {
+    pub id: u64,
+    pub previous_link: String,
+    pub link: String,
+    pub changed_at: DateTime<Utc>,
+    pub changed_by: RevisionActor,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionActor {
+    Master,
+    Token(u64),
+}
+
+impl From<Option<u64>> for RevisionActor {
+    fn from(token: Option<u64>) -> Self {
+        match token {
+            Some(id) => Self::Token(id),
+            None => Self::Master,
+        }
+    }
 }
 
 mod serde_hash {
     use blake3::Hash;
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-    pub fn serialize<S: Serializer>(hash: &Hash, ser: S) -> Result<S::Ok, S::Error> {
-        hash.to_hex().serialize(ser)
+    pub fn serialize<S: Serializer>(hash: &Option<Hash>, ser: S) -> Result<S::Ok, S::Error> {
+        hash.map(|hash| hash.to_hex().to_string()).serialize(ser)
     }
 
-    pub fn deserialize<'de, D: Deserializer<'de>>(des: D) -> Result<Hash, D::Error> {
-        Hash::from_hex(String::deserialize(des)?).map_err(serde::de::Error::custom)
+    pub fn deserialize<'de, D: Deserializer<'de>>(des: D) -> Result<Option<Hash>, D::Error> {
+        Option::<String>::deserialize(des)?
+            .map(|hex| Hash::from_hex(hex).map_err(serde::de::Error::custom))
+            .transpose()
     }
 }
@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkList {
+    pub links: Vec<LinkListEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkListEntry {
+    pub id: String,
+    pub link: String,
+    pub created_at: DateTime<Utc>,
+    pub owner: Option<u64>,
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::maintenance::MaintenanceJob;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MaintenanceJobList {
+    pub jobs: Vec<MaintenanceJobStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceJobStatus {
+    pub job: MaintenanceJob,
+    /// Cron schedule, jobs without one only run when triggered
+    pub schedule: Option<String>,
+    pub running: bool,
+    /// Runs since startup
+    pub runs: u64,
+    pub failures: u64,
+    pub last_run: Option<MaintenanceJobRun>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceJobRun {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Run on demand instead of by its schedule
+    pub triggered: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows_affected: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
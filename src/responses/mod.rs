@@ -1,4 +1,15 @@
+pub mod api_error;
+pub mod audit_events;
+pub mod bulk_created_links;
+pub mod cache_stats;
 pub mod created_link;
+pub mod link_creation_error;
 pub mod link_info;
+pub mod link_list;
+pub mod maintenance;
+pub mod password_form;
+pub mod qr_code;
 pub mod short_link_redirect;
+pub mod strikes;
 pub mod token_created;
+pub mod token_list;
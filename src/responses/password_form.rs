@@ -0,0 +1,48 @@
+use axum::{
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+
+/// Page asking for the password of a protected link. The form is submitted back to the link
+/// itself.
+pub struct PasswordForm {
+    pub id: String,
+    pub failed: bool,
+}
+
+impl IntoResponse for PasswordForm {
+    fn into_response(self) -> Response {
+        let (status, message) = if self.failed {
+            (
+                StatusCode::UNAUTHORIZED,
+                "<p>Incorrect password, try again.</p>",
+            )
+        } else {
+            (StatusCode::OK, "")
+        };
+        // Link ids only contain base58 characters, no escaping is needed
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>Password required</title>
+</head>
+<body>
+<h1>Password required</h1>
+<p>The link <code>{}</code> is protected with a password.</p>
+{}
+<form method="post">
+<input type="password" name="password" autofocus required>
+<button type="submit">Continue</button>
+</form>
+</body>
+</html>
+"#,
+            self.id, message
+        );
+        (status, Html(body)).into_response()
+    }
+}
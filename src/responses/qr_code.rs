@@ -0,0 +1,50 @@
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::json_schemas::qr_params::QrFormat;
+
+/// Cached briefly and only by the client, so a disabled link's code doesn't outlive it in shared
+/// caches
+const QR_CACHE_MAX_AGE: u32 = 5 * 60;
+
+pub enum QrCodeResponse {
+    Image {
+        format: QrFormat,
+        etag: String,
+        body: Vec<u8>,
+    },
+    /// The client's cached image with the same ETag is still valid
+    NotModified { etag: String },
+}
+
+impl IntoResponse for QrCodeResponse {
+    fn into_response(self) -> Response {
+        let (status, etag, content) = match self {
+            Self::Image { format, etag, body } => (StatusCode::OK, etag, Some((format, body))),
+            Self::NotModified { etag } => (StatusCode::NOT_MODIFIED, etag, None),
+        };
+        let response = Response::builder()
+            .status(status)
+            .header(header::ETAG, etag)
+            .header(
+                header::CACHE_CONTROL,
+                format!("private, max-age={QR_CACHE_MAX_AGE}"),
+            )
+            .header(header::VARY, "Accept");
+        match content {
+            Some((format, body)) => response
+                .header(
+                    header::CONTENT_TYPE,
+                    match format {
+                        QrFormat::Svg => "image/svg+xml",
+                        QrFormat::Png => "image/png",
+                    },
+                )
+                .body(body.into())
+                .unwrap(),
+            None => response.body(Default::default()).unwrap(),
+        }
+    }
+}
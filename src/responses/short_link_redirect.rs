@@ -5,14 +5,22 @@ use axum::{
 
 pub struct ShortLinkRedirect {
     pub location: String,
+    pub status: StatusCode,
+    /// Sent as `Cache-Control` `max-age`, in seconds
+    pub cache_max_age: Option<u32>,
 }
 
 impl IntoResponse for ShortLinkRedirect {
     fn into_response(self) -> Response {
-        Response::builder()
-            .status(StatusCode::FOUND)
-            .header(axum::http::header::LOCATION, self.location)
-            .body(Default::default())
-            .unwrap()
+        let mut response = Response::builder()
+            .status(self.status)
+            .header(axum::http::header::LOCATION, self.location);
+        if let Some(max_age) = self.cache_max_age {
+            response = response.header(
+                axum::http::header::CACHE_CONTROL,
+                format!("public, max-age={max_age}"),
+            );
+        }
+        response.body(Default::default()).unwrap()
     }
 }
@@ -0,0 +1,36 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::strikes::StrikeStatus;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Strikes {
+    pub ip: IpAddr,
+    pub amount: u16,
+    /// Whether the IP can't create links
+    #[serde(default)]
+    pub banned: bool,
+    /// When the ban lifts, omitted if it doesn't lift on its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub banned_until: Option<DateTime<Utc>>,
+}
+
+impl Strikes {
+    pub fn new(ip: IpAddr, status: StrikeStatus) -> Self {
+        Self {
+            ip,
+            amount: status.amount,
+            banned: status.banned,
+            banned_until: status.banned_until,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StrikesList {
+    pub strikes: Vec<Strikes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<IpAddr>,
+}
@@ -1,8 +1,11 @@
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
+    Json,
 };
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TokenCreated {
     pub token: String,
 }
@@ -15,3 +18,12 @@ impl IntoResponse for TokenCreated {
             .unwrap()
     }
 }
+
+/// Created token in a JSON body
+pub struct TokenCreatedJson(pub TokenCreated);
+
+impl IntoResponse for TokenCreatedJson {
+    fn into_response(self) -> Response {
+        (StatusCode::CREATED, Json(self.0)).into_response()
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{json_schemas::token_permissions::TokenPermissions, tokens::TokenEntry};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenList {
+    pub tokens: Vec<TokenListEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<u64>,
+}
+
+/// Stored token, the token itself is never shown again after creation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenListEntry {
+    pub id: u64,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub perms: TokenPermissions,
+}
+
+impl From<TokenEntry> for TokenListEntry {
+    fn from(entry: TokenEntry) -> Self {
+        Self {
+            id: entry.id,
+            created_at: entry.created_at,
+            expires_at: entry.expires_at,
+            perms: TokenPermissions {
+                admin_perm: entry.admin_perm,
+                create_link_perm: entry.create_link_perm,
+                view_ips_perm: entry.view_ips_perm,
+            },
+        }
+    }
+}
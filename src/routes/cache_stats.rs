@@ -0,0 +1,21 @@
+use axum::{extract::State, Json};
+
+use crate::{links::cache::LinkCacheStats, responses::cache_stats::CacheStats, ServiceState};
+
+pub async fn cache_stats_route(
+    State(ServiceState {
+        config, link_cache, ..
+    }): State<ServiceState>,
+) -> Json<CacheStats> {
+    let LinkCacheStats {
+        entries,
+        hits,
+        misses,
+    } = link_cache.stats();
+    Json(CacheStats {
+        capacity: config.load().redirect_cache.capacity,
+        entries,
+        hits,
+        misses,
+    })
+}
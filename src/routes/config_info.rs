@@ -7,61 +7,164 @@ use axum::{
     Json,
 };
 use axum_extra::TypedHeader;
-use chrono::Duration;
-use serde::{Serialize, Serializer};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 
-use crate::{custom_headers::accept::Accept, service_config::ServiceConfig, ServiceState};
+use crate::{
+    custom_headers::accept::Accept,
+    json_schemas::redirect_code::RedirectCode,
+    service_config::{
+        duration::{
+            deserialize_duration, deserialize_optional_duration, format_duration,
+            serialize_duration, serialize_optional_duration,
+        },
+        reload::SharedConfig,
+        ServiceConfig,
+    },
+    ServiceState,
+};
 
 #[derive(Debug, Clone)]
 pub enum ConfigInfoResponse {
-    Json(Json<ConfigInfo>),
+    Json(Box<Json<ConfigInfo>>),
     String(String),
 }
 
 impl IntoResponse for ConfigInfoResponse {
     fn into_response(self) -> Response {
         match self {
-            Self::Json(json) => json.into_response(),
+            Self::Json(json) => (*json).into_response(),
             Self::String(string) => string.into_response(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigInfo {
-    pub service_version: &'static str,
+    pub service_version: String,
     pub max_strikes: u16,
+    /// Expiry of strikes and bans, only with IP recording
+    #[serde(default)]
+    pub strikes: Option<StrikesConfigInfo>,
+    pub max_bulk_links: u16,
     pub log_level: log::Level,
     pub ip_recording: Option<IpRecordingConfigInfo>,
     pub tokens: Option<TokenConfigInfo>,
+    pub link_passwords: LinkPasswordsConfigInfo,
+    pub redirects: RedirectsConfigInfo,
+    pub redirect_cache: RedirectCacheConfigInfo,
+    pub domains: Vec<DomainConfigInfo>,
+    pub routes: RoutesConfigInfo,
+    pub frontend: Option<FrontendConfigInfo>,
+    /// Times the config was reloaded since startup
+    #[serde(default)]
+    pub reload_count: u64,
+    #[serde(default)]
+    pub last_reload: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontendConfigInfo {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutesConfigInfo {
+    pub link_prefix: String,
+    pub api_prefix: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IpRecordingConfigInfo {
-    #[serde(serialize_with = "duration_to_str_serialize")]
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
     pub retention_period: Duration,
     pub retention_check_period: Arc<str>,
 }
 
-fn duration_to_str_serialize<S: Serializer>(dur: &Duration, ser: S) -> Result<S::Ok, S::Error> {
-    dur.to_string().serialize(ser)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrikesConfigInfo {
+    #[serde(
+        serialize_with = "serialize_optional_duration",
+        deserialize_with = "deserialize_optional_duration"
+    )]
+    pub decay_period: Option<Duration>,
+    #[serde(
+        serialize_with = "serialize_optional_duration",
+        deserialize_with = "deserialize_optional_duration"
+    )]
+    pub ban_duration: Option<Duration>,
+    pub ban_escalation: f64,
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub max_ban_duration: Duration,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPasswordsConfigInfo {
+    pub max_failed_attempts: u32,
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub lockout_period: Duration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectsConfigInfo {
+    pub default_code: RedirectCode,
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub permanent_cache_max_age: Duration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectCacheConfigInfo {
+    pub capacity: usize,
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub ttl: Duration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainConfigInfo {
+    pub host: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    pub id_length: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_creation_requires_auth: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_code: Option<RedirectCode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenConfigInfo {
-    link_creation_requires_auth: bool,
+    pub link_creation_requires_auth: bool,
 }
 
 pub async fn config_info_route(
-    State(ServiceState { db: _, config }): State<ServiceState>,
+    State(ServiceState { config, .. }): State<ServiceState>,
     accept: Option<TypedHeader<Accept>>,
 ) -> Result<ConfigInfoResponse, (StatusCode, String)> {
     let accept = accept.unwrap_or(TypedHeader(Accept(mime::TEXT_PLAIN)));
 
     if accept.0 .0 == mime::APPLICATION_JSON {
-        Ok(ConfigInfoResponse::Json(config_info_json_handler(config)))
+        Ok(ConfigInfoResponse::Json(Box::new(
+            config_info_json_handler(&config),
+        )))
     } else if accept.0 .0 == mime::TEXT_PLAIN {
-        Ok(ConfigInfoResponse::String(config_info_text_handler(config)))
+        Ok(ConfigInfoResponse::String(config_info_text_handler(
+            &config,
+        )))
     } else {
         Err((
             StatusCode::BAD_REQUEST,
@@ -70,10 +173,19 @@ pub async fn config_info_route(
     }
 }
 
-pub fn config_info_json_handler(config: ServiceConfig) -> Json<ConfigInfo> {
+pub fn config_info_json_handler(config: &SharedConfig) -> Json<ConfigInfo> {
+    let (config, reloads) = config.load_with_stats();
+    let config = ServiceConfig::clone(&config);
     Json(ConfigInfo {
-        service_version: env!("CARGO_PKG_VERSION"),
+        service_version: env!("CARGO_PKG_VERSION").to_owned(),
         max_strikes: config.max_strikes,
+        strikes: config.ip_recording.is_some().then_some(StrikesConfigInfo {
+            decay_period: config.strikes.decay_period,
+            ban_duration: config.strikes.ban_duration,
+            ban_escalation: config.strikes.ban_escalation,
+            max_ban_duration: config.strikes.max_ban_duration,
+        }),
+        max_bulk_links: config.max_bulk_links,
         log_level: log::max_level()
             .to_level()
             .expect("Logging shouldn't be turned off"),
@@ -84,16 +196,62 @@ pub fn config_info_json_handler(config: ServiceConfig) -> Json<ConfigInfo> {
         tokens: config.token_config.map(|tkc| TokenConfigInfo {
             link_creation_requires_auth: tkc.creation_requires_auth,
         }),
+        link_passwords: LinkPasswordsConfigInfo {
+            max_failed_attempts: config.link_passwords.max_failed_attempts,
+            lockout_period: config.link_passwords.lockout_period,
+        },
+        redirects: RedirectsConfigInfo {
+            default_code: config.redirects.default_code,
+            permanent_cache_max_age: config.redirects.permanent_cache_max_age,
+        },
+        redirect_cache: RedirectCacheConfigInfo {
+            capacity: config.redirect_cache.capacity,
+            ttl: config.redirect_cache.ttl,
+        },
+        domains: config
+            .domains
+            .into_iter()
+            .map(|domain| DomainConfigInfo {
+                host: domain.host,
+                base_url: domain.base_url,
+                id_length: domain.id_length,
+                link_creation_requires_auth: domain.creation_requires_auth,
+                redirect_code: domain.redirect_code,
+            })
+            .collect(),
+        routes: RoutesConfigInfo {
+            link_prefix: config.routes.link_prefix,
+            api_prefix: config.routes.api_prefix,
+        },
+        frontend: config.frontend.map(|frontend| FrontendConfigInfo {
+            path: frontend.path,
+        }),
+        reload_count: reloads.count,
+        last_reload: reloads.last_reload,
     })
 }
 
-pub fn config_info_text_handler(config: ServiceConfig) -> String {
+pub fn config_info_text_handler(config: &SharedConfig) -> String {
+    let (config, reloads) = config.load_with_stats();
     let ServiceConfig {
         max_strikes,
+        strikes,
+        max_bulk_links,
         ip_recording,
         token_config: tokens,
         log_level: _,
-    } = config;
+        logging: _,
+        audit_log: _,
+        maintenance: _,
+        link_passwords,
+        redirects,
+        redirect_cache,
+        domains,
+        routes,
+        frontend,
+        server: _,
+        sources: _,
+    } = ServiceConfig::clone(&config);
     let ip_recording_status = if ip_recording.is_some() {
         "Enabled"
     } else {
@@ -104,6 +262,34 @@ pub fn config_info_text_handler(config: ServiceConfig) -> String {
     } else {
         "Disabled"
     };
+    let domains = if domains.is_empty() {
+        "Any".to_string()
+    } else {
+        domains
+            .iter()
+            .map(|domain| domain.host.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let frontend_status = match &frontend {
+        Some(frontend) => format!("Served at {:?}", frontend.path),
+        None => "Disabled".to_string(),
+    };
+    let last_reload = reloads
+        .last_reload
+        .map_or_else(|| "Never".to_string(), |time| time.to_rfc3339());
+    let strike_decay = strikes
+        .decay_period
+        .map_or_else(|| "Never".to_string(), format_duration);
+    let ban_duration = match strikes.ban_duration {
+        Some(ban_duration) => format!(
+            "{}, escalating {}x up to {}",
+            format_duration(ban_duration),
+            strikes.ban_escalation,
+            format_duration(strikes.max_ban_duration)
+        ),
+        None => "Until strikes expire".to_string(),
+    };
     let creation_requires_auth = if let Some(toks) = tokens {
         toks.creation_requires_auth
     } else {
@@ -115,16 +301,46 @@ pub fn config_info_text_handler(config: ServiceConfig) -> String {
 
         IP recording: {};
         Max amount of strikes: {};
+        Strike expiry period: {};
+        Ban duration: {};
+        Max links per bulk request: {};
         Token authentication: {};
         Link creation requires authentication: {};
+        Failed link password attempts before lockout: {};
+        Link password lockout period: {};
+        Default redirect code: {};
+        Permanent redirect cache max age: {};
+        Redirect cache capacity: {};
+        Redirect cache TTL: {};
+        Short domains: {};
+        Short link prefix: {:?};
+        API prefix: {:?};
+        Web frontend: {};
 
         Log level: {}
+        Config reloads: {}
+        Last config reload: {}
         "#,
         env!("CARGO_PKG_VERSION"),
         ip_recording_status,
         max_strikes,
+        strike_decay,
+        ban_duration,
+        max_bulk_links,
         tokens_status,
         creation_requires_auth,
+        link_passwords.max_failed_attempts,
+        format_duration(link_passwords.lockout_period),
+        u16::from(redirects.default_code),
+        format_duration(redirects.permanent_cache_max_age),
+        redirect_cache.capacity,
+        format_duration(redirect_cache.ttl),
+        domains,
+        routes.link_prefix,
+        routes.api_prefix,
+        frontend_status,
         log::max_level(),
+        reloads.count,
+        last_reload,
     )
 }
@@ -0,0 +1,68 @@
+use std::net::SocketAddr;
+
+use axum::{
+    body::Bytes,
+    extract::{ConnectInfo, Query, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization, ContentType};
+
+use crate::{
+    custom_headers::link_password::LinkPassword,
+    json_schemas::create_link_params::CreateLinkParams,
+    links::domain::LinkDomain,
+    responses::{bulk_created_links::BulkCreatedLinks, link_creation_error::LinkCreationError},
+    service::links::create_links,
+    ServiceState,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create_links_bulk_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    Query(params): Query<CreateLinkParams>,
+    link_password: Option<TypedHeader<LinkPassword>>,
+    content_type: Option<TypedHeader<ContentType>>,
+    body: Bytes,
+) -> Result<BulkCreatedLinks, LinkCreationError> {
+    let urls = parse_bulk_body(content_type.map(|TypedHeader(ct)| ct), &body)?;
+
+    Ok(BulkCreatedLinks(
+        create_links(
+            &state,
+            &domain,
+            addr.ip(),
+            auth_header.as_ref().map(|auth| auth.token()),
+            params,
+            link_password.map(|TypedHeader(LinkPassword(password))| password),
+            urls,
+        )
+        .await?,
+    ))
+}
+
+/// Links are accepted either as a JSON array of strings or as newline-delimited plain text
+fn parse_bulk_body(
+    content_type: Option<ContentType>,
+    body: &Bytes,
+) -> Result<Vec<String>, StatusCode> {
+    if content_type.is_some_and(|ct| {
+        mime::Mime::from(ct).essence_str() == mime::APPLICATION_JSON.essence_str()
+    }) {
+        let Json(urls) =
+            Json::<Vec<String>>::from_bytes(body).map_err(|_| StatusCode::BAD_REQUEST)?;
+        Ok(urls)
+    } else {
+        Ok(std::str::from_utf8(body)
+            .map_err(|_| StatusCode::BAD_REQUEST)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect())
+    }
+}
@@ -1,24 +1,261 @@
-use crate::{responses::short_link_redirect::ShortLinkRedirect, ServiceState};
+use std::net::SocketAddr;
+
+use crate::{
+    json_schemas::redirect_code::RedirectCode,
+    links::{
+        cache::{CachedLink, LinkCache},
+        domain::LinkDomain,
+        is_deleted,
+        password::verify_password,
+        redirect::{redirect_after_form, redirect_to, RedirectOptions},
+    },
+    responses::{password_form::PasswordForm, short_link_redirect::ShortLinkRedirect},
+    service_config::domains::DomainConfig,
+    ServiceState,
+};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{ConnectInfo, Path, RawQuery, State},
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Form,
 };
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::{MySql, Pool};
+
+pub enum GetLinkResponse {
+    Redirect(ShortLinkRedirect),
+    PasswordForm(PasswordForm),
+}
+
+impl IntoResponse for GetLinkResponse {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Redirect(redirect) => redirect.into_response(),
+            Self::PasswordForm(form) => form.into_response(),
+        }
+    }
+}
+
+/// Short link, optionally followed by a path to append to the destination
+#[derive(Debug, Deserialize)]
+pub struct LinkPath {
+    id: String,
+    #[serde(default)]
+    path: Option<String>,
+}
+
+impl LinkPath {
+    /// Path after the id as it was requested, without percent-decoding
+    fn raw_suffix<'a>(&self, uri: &'a Uri) -> Option<&'a str> {
+        self.path.as_ref()?;
+        uri.path()
+            .split_once(&format!("/{}/", self.id))
+            .map(|(_, suffix)| suffix)
+    }
+}
 
 #[derive(Debug)]
 struct LinkQuery {
     link: String,
+    disabled_at: Option<DateTime<Utc>>,
+    quarantined_at: Option<DateTime<Utc>>,
+    password_hash: Option<String>,
+    redirect_code: Option<u16>,
+    forward_query: bool,
+    forward_path: bool,
+    cache_max_age: Option<u32>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl LinkQuery {
+    fn into_cached(self) -> Result<CachedLink, StatusCode> {
+        Ok(CachedLink {
+            options: RedirectOptions {
+                redirect_code: self
+                    .redirect_code
+                    .map(RedirectCode::try_from)
+                    .transpose()
+                    .map_err(|e| {
+                        log::error!("Invalid redirect code stored with link: {e}");
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    })?,
+                forward_query: self.forward_query,
+                forward_path: self.forward_path,
+                cache_max_age: self.cache_max_age,
+            },
+            link: self.link,
+            password_hash: self.password_hash,
+            expires_at: self.expires_at,
+        })
+    }
+}
+
+/// Looks up the link in the cache, falling back to the database. Missing, deleted and disabled
+/// links are cached too. Cached links are checked for expiry on every lookup.
+async fn fetch_link(
+    db: &Pool<MySql>,
+    cache: &LinkCache,
+    domain: &DomainConfig,
+    id: &str,
+) -> Result<CachedLink, StatusCode> {
+    let namespace = domain.namespace();
+    let lookup = match cache.get(namespace, id) {
+        Some(lookup) => lookup,
+        None => {
+            let generation = cache.generation();
+            let lookup = query_link(db, namespace, id).await;
+            if !matches!(lookup, Err(StatusCode::INTERNAL_SERVER_ERROR)) {
+                cache.insert(namespace, id, lookup.clone(), generation);
+            }
+            lookup
+        }
+    };
+    let mut link = lookup?;
+    if is_expired(link.expires_at) {
+        return Err(StatusCode::GONE);
+    }
+    link.options.redirect_code = link.options.redirect_code.or(domain.redirect_code);
+    Ok(link)
+}
+
+#[tracing::instrument(skip_all)]
+async fn query_link(db: &Pool<MySql>, namespace: &str, id: &str) -> Result<CachedLink, StatusCode> {
+    let Some(link) = sqlx::query_as!(
+        LinkQuery,
+        r#"
+        SELECT
+            link,
+            disabled_at,
+            quarantined_at,
+            password_hash,
+            redirect_code,
+            forward_query AS `forward_query: _`,
+            forward_path AS `forward_path: _`,
+            cache_max_age,
+            expires_at
+        FROM links
+        WHERE domain = ? AND id = ?
+        "#,
+        namespace,
+        id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| {
+        log::error!("Error querying link: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    else {
+        return Err(if is_deleted(db, namespace, id).await? {
+            StatusCode::GONE
+        } else {
+            StatusCode::NOT_FOUND
+        });
+    };
+    if link.disabled_at.is_some() || link.quarantined_at.is_some() || is_expired(link.expires_at) {
+        return Err(StatusCode::GONE);
+    }
+    link.into_cached()
+}
+
+fn is_expired(expires_at: Option<DateTime<Utc>>) -> bool {
+    expires_at.is_some_and(|expires_at| expires_at <= Utc::now())
 }
 
 pub async fn get_link_route(
-    State(ServiceState { db, config: _ }): State<ServiceState>,
-    Path(id): Path<String>,
-) -> Result<ShortLinkRedirect, StatusCode> {
-    let LinkQuery { link } = sqlx::query_as!(LinkQuery, "SELECT link FROM links WHERE id = ?", id)
-        .fetch_one(db.as_ref())
-        .await
-        .map_err(|e| match e {
-            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
-    Ok(ShortLinkRedirect { location: link })
+    State(ServiceState {
+        db,
+        config,
+        link_cache,
+        ..
+    }): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    Path(link_path): Path<LinkPath>,
+    RawQuery(query): RawQuery,
+    uri: Uri,
+) -> Result<GetLinkResponse, StatusCode> {
+    let CachedLink {
+        link,
+        password_hash,
+        options,
+        ..
+    } = fetch_link(db.as_ref(), &link_cache, &domain, &link_path.id).await?;
+    let suffix = link_path.raw_suffix(&uri);
+    if suffix.is_some_and(|suffix| !suffix.is_empty()) && !options.forward_path {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if password_hash.is_some() {
+        return Ok(GetLinkResponse::PasswordForm(PasswordForm {
+            id: link_path.id,
+            failed: false,
+        }));
+    }
+    Ok(GetLinkResponse::Redirect(redirect_to(
+        &link,
+        &options,
+        &config.load().redirects,
+        suffix,
+        query.as_deref(),
+    )?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnlockParams {
+    password: String,
+}
+
+/// Submission of the password form of a protected link
+pub async fn unlock_link_route(
+    State(ServiceState {
+        db,
+        config,
+        password_attempts,
+        link_cache,
+        ..
+    }): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(link_path): Path<LinkPath>,
+    RawQuery(query): RawQuery,
+    uri: Uri,
+    Form(UnlockParams { password }): Form<UnlockParams>,
+) -> Result<GetLinkResponse, StatusCode> {
+    let CachedLink {
+        link,
+        password_hash,
+        options,
+        ..
+    } = fetch_link(db.as_ref(), &link_cache, &domain, &link_path.id).await?;
+    let suffix = link_path.raw_suffix(&uri);
+    let Some(password_hash) = password_hash else {
+        return Ok(GetLinkResponse::Redirect(redirect_after_form(
+            &link,
+            &options,
+            suffix,
+            query.as_deref(),
+        )?));
+    };
+
+    let ip = addr.ip();
+    let config = config.load();
+    if password_attempts.is_locked_out(ip, &config.link_passwords) {
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    if verify_password(password, password_hash).await? {
+        password_attempts.clear(ip);
+        Ok(GetLinkResponse::Redirect(redirect_after_form(
+            &link,
+            &options,
+            suffix,
+            query.as_deref(),
+        )?))
+    } else {
+        password_attempts.record_failure(ip, &config.link_passwords);
+        Ok(GetLinkResponse::PasswordForm(PasswordForm {
+            id: link_path.id,
+            failed: true,
+        }))
+    }
 }
@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, uri::Authority, HeaderMap, StatusCode, Uri},
+};
+use axum_extra::TypedHeader;
+use headers::IfNoneMatch;
+
+use crate::{
+    json_schemas::qr_params::{QrFormat, QrParams},
+    links::domain::{request_host, LinkDomain},
+    responses::qr_code::QrCodeResponse,
+    service::link_qr::link_qr_code,
+    service_config::server::ServerConfig,
+    ServiceState,
+};
+
+/// Format requested through the query, otherwise PNG if only that is accepted, SVG by default
+pub fn qr_format(params: &QrParams, headers: &HeaderMap) -> QrFormat {
+    if let Some(format) = params.format {
+        return format;
+    }
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .unwrap_or_default();
+    if accept.contains("image/png") && !accept.contains("image/svg+xml") {
+        QrFormat::Png
+    } else {
+        QrFormat::Svg
+    }
+}
+
+/// Origin of the request, used for short links without an absolute base URL. The host comes
+/// from the `Host` header, the scheme from `X-Forwarded-Proto` behind a trusted proxy, otherwise
+/// it's `https` with TLS and `http` without. `None` if either isn't valid.
+pub fn request_origin(server: &ServerConfig, headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    let host = request_host(headers, uri)?
+        .parse::<Authority>()
+        .ok()
+        .filter(|host| !host.as_str().contains('@'))?;
+    let forwarded_proto = headers
+        .get("X-Forwarded-Proto")
+        .filter(|_| server.trusted_proxy);
+    let scheme = match forwarded_proto {
+        Some(proto) => match proto.to_str().ok()? {
+            "http" => "http",
+            "https" => "https",
+            _ => return None,
+        },
+        None if server.tls.is_some() => "https",
+        None => "http",
+    };
+    Some(format!("{scheme}://{host}"))
+}
+
+pub async fn get_link_qr_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    uri: Uri,
+    headers: HeaderMap,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    Path(id): Path<String>,
+    Query(params): Query<QrParams>,
+) -> Result<QrCodeResponse, StatusCode> {
+    let origin = request_origin(&state.config.load().server, &headers, &uri);
+    link_qr_code(
+        &state,
+        &domain,
+        origin.as_deref(),
+        &id,
+        qr_format(&params, &headers),
+        params,
+        if_none_match.as_ref().map(|TypedHeader(header)| header),
+    )
+    .await
+}
@@ -0,0 +1,23 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization};
+
+use crate::{
+    json_schemas::list_links_params::ListLinksParams, links::domain::LinkDomain,
+    responses::link_list::LinkList, service::link_list::list_links, ServiceState,
+};
+
+pub async fn list_links_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    auth_header: TypedHeader<Authorization<Bearer>>,
+    Query(params): Query<ListLinksParams>,
+) -> Result<Json<LinkList>, StatusCode> {
+    list_links(&state, &domain, auth_header.token(), params)
+        .await
+        .map(Json)
+}
@@ -0,0 +1,74 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::StatusCode,
+};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization};
+
+use crate::{
+    links::domain::LinkDomain,
+    service::links::{delete_link, disable_link, enable_link, revert_link, update_link},
+    ServiceState,
+};
+
+pub async fn update_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+    url: String,
+) -> Result<(), StatusCode> {
+    update_link(&state, &domain, addr.ip(), auth_header.token(), &id, &url).await
+}
+
+pub async fn revert_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: TypedHeader<Authorization<Bearer>>,
+    Path((id, revision)): Path<(String, u64)>,
+) -> Result<(), StatusCode> {
+    revert_link(
+        &state,
+        &domain,
+        addr.ip(),
+        auth_header.token(),
+        &id,
+        revision,
+    )
+    .await
+}
+
+pub async fn delete_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    delete_link(&state, &domain, addr.ip(), auth_header.token(), &id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn disable_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+) -> Result<(), StatusCode> {
+    disable_link(&state, &domain, addr.ip(), auth_header.token(), &id).await
+}
+
+pub async fn enable_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<String>,
+) -> Result<(), StatusCode> {
+    enable_link(&state, &domain, addr.ip(), auth_header.token(), &id).await
+}
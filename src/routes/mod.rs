@@ -1,37 +1,104 @@
 use axum::{
-    routing::{get, post},
+    routing::{get, patch, post},
     Router,
 };
+use tower_http::services::{ServeDir, ServeFile};
 
 use crate::{
     routes::{
-        config_info::config_info_route, create_link::create_link_route,
-        create_token::create_token_route, get_link::get_link_route,
-        get_link_info::get_link_info_route, revoke_token::revoke_token_route,
+        cache_stats::cache_stats_route,
+        config_info::config_info_route,
+        create_link::create_link_route,
+        create_links_bulk::create_links_bulk_route,
+        create_token::create_token_route,
+        get_link::{get_link_route, unlock_link_route},
+        get_link_info::get_link_info_route,
+        get_link_qr::get_link_qr_route,
+        list_links::list_links_route,
+        manage_link::{
+            delete_link_route, disable_link_route, enable_link_route, revert_link_route,
+            update_link_route,
+        },
+        revoke_token::revoke_token_route,
     },
     service_config::ServiceConfig,
     ServiceState,
 };
 
+pub mod cache_stats;
 pub mod config_info;
 pub mod create_link;
+pub mod create_links_bulk;
 pub mod create_token;
 pub mod get_link;
 pub mod get_link_info;
+pub mod get_link_qr;
+pub mod list_links;
+pub mod manage_link;
+pub mod request_context;
 pub mod revoke_token;
+pub mod v1;
 
 pub fn create_router(config: &ServiceConfig) -> Router<ServiceState> {
     log::info!("Building router");
-    let mut router = Router::new()
-        .route("/l/create", post(create_link_route))
-        .route("/l/:id", get(get_link_route))
-        .route("/l/:id/info", get(get_link_info_route))
-        .route("/l/config_info", get(config_info_route));
+    let mut router = Router::new();
 
-    if config.token_config.is_some() {
+    for prefix in config.routes.link_prefixes() {
         router = router
-            .route("/l/tokens/create", post(create_token_route))
-            .route("/l/tokens/revoke", post(revoke_token_route));
+            .route(
+                &format!("{prefix}/{{id}}"),
+                get(get_link_route).post(unlock_link_route),
+            )
+            .route(
+                &format!("{prefix}/{{id}}/{{*path}}"),
+                get(get_link_route).post(unlock_link_route),
+            );
+    }
+
+    for prefix in config.routes.api_prefixes() {
+        router = router
+            .route(&format!("{prefix}/create"), post(create_link_route))
+            .route(
+                &format!("{prefix}/create/bulk"),
+                post(create_links_bulk_route),
+            )
+            .route(&format!("{prefix}/{{id}}/info"), get(get_link_info_route))
+            .route(&format!("{prefix}/{{id}}/qr"), get(get_link_qr_route))
+            .route(&format!("{prefix}/config_info"), get(config_info_route))
+            .route(&format!("{prefix}/cache_stats"), get(cache_stats_route));
+
+        if config.token_config.is_some() {
+            router = router
+                .route(&format!("{prefix}/tokens/create"), post(create_token_route))
+                .route(&format!("{prefix}/tokens/revoke"), post(revoke_token_route))
+                .route(&format!("{prefix}/links"), get(list_links_route))
+                .route(
+                    &format!("{prefix}/{{id}}"),
+                    patch(update_link_route).delete(delete_link_route),
+                )
+                .route(
+                    &format!("{prefix}/{{id}}/disable"),
+                    post(disable_link_route),
+                )
+                .route(&format!("{prefix}/{{id}}/enable"), post(enable_link_route))
+                .route(
+                    &format!("{prefix}/{{id}}/revisions/{{revision}}/revert"),
+                    post(revert_link_route),
+                );
+        }
+    }
+
+    router = router.nest("/api/v1", v1::create_router(config));
+
+    if let Some(frontend) = &config.frontend {
+        // Paths of the single page app that aren't files are answered with `index.html`
+        let assets = ServeDir::new(&frontend.assets_dir)
+            .fallback(ServeFile::new(frontend.assets_dir.join("index.html")));
+        router = if frontend.path.is_empty() {
+            router.fallback_service(assets)
+        } else {
+            router.nest_service(&frontend.path, assets)
+        };
     }
 
     router
@@ -0,0 +1,125 @@
+use std::{net::SocketAddr, time::Instant};
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{field::Empty, Instrument};
+
+use crate::{service_config::logging::AccessLogField, ServiceState};
+
+/// Header carrying the id of a request
+pub static REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Gives the request an id, the one of its `X-Request-Id` header if it's valid or a random one,
+/// and handles it in a span with the id, so that every line logged for it carries the id. The id
+/// is returned in `X-Request-Id`. Writes the access log line of the request, if it's enabled.
+pub async fn request_context(
+    State(ServiceState { config, .. }): State<ServiceState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID)
+        .and_then(|id| id.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map_or_else(|| format!("{:032x}", rand::random::<u128>()), str::to_owned);
+    let span = tracing::info_span!("request", request_id = %request_id);
+
+    let access_log = config.load().logging.access_log.as_ref().map(|access_log| {
+        (
+            span.in_scope(|| access_span(&access_log.fields, &request, addr)),
+            access_log.fields.clone(),
+        )
+    });
+    let start = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+
+    if let Ok(id) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID.clone(), id);
+    }
+    if let Some((access_span, fields)) = access_log {
+        if fields.contains(&AccessLogField::Status) {
+            access_span.record("status", response.status().as_u16());
+        }
+        if fields.contains(&AccessLogField::LatencyMs) {
+            let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+            access_span.record("latency_ms", (latency_ms * 1000.0).round() / 1000.0);
+        }
+        access_span.in_scope(|| tracing::info!(target: "sayless::access", "Request handled"));
+    }
+    response
+}
+
+/// Ids are limited to characters that can't break up log lines
+fn is_valid_request_id(id: &str) -> bool {
+    (1..=MAX_REQUEST_ID_LENGTH).contains(&id.len())
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.:/+=".contains(&b))
+}
+
+/// Span with the access log fields of the request, a child of the current span. Only the selected
+/// fields are recorded, status and latency once the request is handled.
+fn access_span(fields: &[AccessLogField], request: &Request, addr: SocketAddr) -> tracing::Span {
+    let span = tracing::info_span!(
+        target: "sayless::access",
+        "access",
+        client_ip = Empty,
+        method = Empty,
+        path = Empty,
+        query = Empty,
+        route = Empty,
+        host = Empty,
+        user_agent = Empty,
+        referer = Empty,
+        status = Empty,
+        latency_ms = Empty,
+    );
+    let header = |name: &header::HeaderName| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+    for field in fields {
+        match field {
+            AccessLogField::ClientIp => {
+                span.record("client_ip", tracing::field::display(addr.ip()));
+            }
+            AccessLogField::Method => {
+                span.record("method", request.method().as_str());
+            }
+            AccessLogField::Path => {
+                span.record("path", request.uri().path());
+            }
+            AccessLogField::Query => {
+                span.record("query", request.uri().query().unwrap_or_default());
+            }
+            AccessLogField::Route => {
+                if let Some(route) = request.extensions().get::<MatchedPath>() {
+                    span.record("route", route.as_str());
+                }
+            }
+            AccessLogField::Host => {
+                span.record("host", header(&header::HOST));
+            }
+            AccessLogField::UserAgent => {
+                span.record("user_agent", header(&header::USER_AGENT));
+            }
+            AccessLogField::Referer => {
+                span.record("referer", header(&header::REFERER));
+            }
+            // Recorded once the request is handled
+            AccessLogField::Status | AccessLogField::LatencyMs => {}
+        }
+    }
+    span
+}
@@ -0,0 +1,20 @@
+use axum::{extract::State, Json};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization};
+
+use super::{extract::ApiQuery, required_token};
+use crate::{
+    json_schemas::list_audit_events_params::ListAuditEventsParams,
+    responses::{api_error::ApiError, audit_events::AuditEventList},
+    service::audit_log::list_audit_events,
+    ServiceState,
+};
+
+pub async fn list_audit_events_route(
+    State(state): State<ServiceState>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiQuery(params): ApiQuery<ListAuditEventsParams>,
+) -> Result<Json<AuditEventList>, ApiError> {
+    let token = required_token(&auth_header)?;
+    Ok(Json(list_audit_events(&state, token, params).await?))
+}
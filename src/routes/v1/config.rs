@@ -0,0 +1,12 @@
+use axum::{extract::State, Json};
+
+use crate::{
+    routes::config_info::{config_info_json_handler, ConfigInfo},
+    ServiceState,
+};
+
+pub async fn config_route(
+    State(ServiceState { config, .. }): State<ServiceState>,
+) -> Json<ConfigInfo> {
+    config_info_json_handler(&config)
+}
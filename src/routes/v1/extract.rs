@@ -0,0 +1,18 @@
+use axum::extract::{FromRequest, FromRequestParts};
+
+use crate::responses::api_error::ApiRejection;
+
+/// `Json` that rejects invalid bodies with the JSON error body of the API
+#[derive(Debug, FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiRejection))]
+pub struct ApiJson<T>(pub T);
+
+/// `Path` that rejects invalid path parameters with the JSON error body of the API
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiRejection))]
+pub struct ApiPath<T>(pub T);
+
+/// `Query` that rejects invalid query strings with the JSON error body of the API
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiRejection))]
+pub struct ApiQuery<T>(pub T);
@@ -0,0 +1,222 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode, Uri},
+    Json,
+};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization, IfNoneMatch};
+
+use super::{
+    extract::{ApiJson, ApiPath, ApiQuery},
+    required_token,
+};
+use crate::{
+    json_schemas::{
+        create_link_request::CreateLinkRequest, create_links_request::CreateLinksRequest,
+        list_links_params::ListLinksParams, qr_params::QrParams,
+        update_link_request::UpdateLinkRequest,
+    },
+    links::domain::LinkDomain,
+    responses::{
+        api_error::ApiError, bulk_created_links::BulkLinkResult, created_link::CreatedLinkJson,
+        link_creation_error::ApiLinkCreationError, link_info::LinkInfo, link_list::LinkList,
+        qr_code::QrCodeResponse,
+    },
+    routes::get_link_qr::{qr_format, request_origin},
+    service::{
+        link_info::link_info,
+        link_list::list_links,
+        link_qr::link_qr_code,
+        links::{
+            create_link, create_links, delete_link, disable_link, enable_link, quarantine_link,
+            release_link, revert_link, update_link,
+        },
+    },
+    ServiceState,
+};
+
+pub async fn create_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiJson(CreateLinkRequest {
+        url,
+        password,
+        params,
+    }): ApiJson<CreateLinkRequest>,
+) -> Result<CreatedLinkJson, ApiLinkCreationError> {
+    Ok(CreatedLinkJson(
+        create_link(
+            &state,
+            &domain,
+            addr.ip(),
+            auth_header.as_ref().map(|auth| auth.token()),
+            params,
+            password,
+            &url,
+        )
+        .await?,
+    ))
+}
+
+pub async fn create_links_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiJson(CreateLinksRequest {
+        urls,
+        password,
+        params,
+    }): ApiJson<CreateLinksRequest>,
+) -> Result<Json<Vec<BulkLinkResult>>, ApiLinkCreationError> {
+    Ok(Json(
+        create_links(
+            &state,
+            &domain,
+            addr.ip(),
+            auth_header.as_ref().map(|auth| auth.token()),
+            params,
+            password,
+            urls,
+        )
+        .await?,
+    ))
+}
+
+pub async fn list_links_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiQuery(params): ApiQuery<ListLinksParams>,
+) -> Result<Json<LinkList>, ApiError> {
+    let token = required_token(&auth_header)?;
+    Ok(Json(list_links(&state, &domain, token, params).await?))
+}
+
+pub async fn link_info_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(id): ApiPath<String>,
+) -> Result<Json<LinkInfo>, ApiError> {
+    Ok(Json(
+        link_info(
+            &state,
+            &domain,
+            auth_header.as_ref().map(|auth| auth.token()),
+            &id,
+        )
+        .await?,
+    ))
+}
+
+pub async fn update_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(id): ApiPath<String>,
+    ApiJson(UpdateLinkRequest { url }): ApiJson<UpdateLinkRequest>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    update_link(&state, &domain, addr.ip(), token, &id, &url).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn revert_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath((id, revision)): ApiPath<(String, u64)>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    revert_link(&state, &domain, addr.ip(), token, &id, revision).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn delete_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(id): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    delete_link(&state, &domain, addr.ip(), token, &id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn disable_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(id): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    disable_link(&state, &domain, addr.ip(), token, &id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn enable_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(id): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    enable_link(&state, &domain, addr.ip(), token, &id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn quarantine_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(id): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    quarantine_link(&state, &domain, addr.ip(), token, &id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn release_link_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(id): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    release_link(&state, &domain, addr.ip(), token, &id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn link_qr_route(
+    State(state): State<ServiceState>,
+    LinkDomain(domain): LinkDomain,
+    uri: Uri,
+    headers: HeaderMap,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    ApiPath(id): ApiPath<String>,
+    ApiQuery(params): ApiQuery<QrParams>,
+) -> Result<QrCodeResponse, ApiError> {
+    let origin = request_origin(&state.config.load().server, &headers, &uri);
+    Ok(link_qr_code(
+        &state,
+        &domain,
+        origin.as_deref(),
+        &id,
+        qr_format(&params, &headers),
+        params,
+        if_none_match.as_ref().map(|TypedHeader(header)| header),
+    )
+    .await?)
+}
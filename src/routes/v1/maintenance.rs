@@ -0,0 +1,41 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization};
+
+use super::{extract::ApiPath, required_token};
+use crate::{
+    maintenance::MaintenanceJob,
+    responses::{
+        api_error::ApiError,
+        maintenance::{MaintenanceJobList, MaintenanceJobRun},
+    },
+    service::maintenance::{list_jobs, trigger_job},
+    ServiceState,
+};
+
+pub async fn list_maintenance_jobs_route(
+    State(state): State<ServiceState>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<Json<MaintenanceJobList>, ApiError> {
+    let token = required_token(&auth_header)?;
+    Ok(Json(list_jobs(&state, token).await?))
+}
+
+pub async fn run_maintenance_job_route(
+    State(state): State<ServiceState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(job): ApiPath<String>,
+) -> Result<Json<MaintenanceJobRun>, ApiError> {
+    let token = required_token(&auth_header)?;
+    let job = job
+        .parse::<MaintenanceJob>()
+        .map_err(|_| ApiError(StatusCode::NOT_FOUND))?;
+    Ok(Json(trigger_job(&state, addr.ip(), token, job).await?))
+}
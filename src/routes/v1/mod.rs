@@ -0,0 +1,89 @@
+use axum::{
+    http::StatusCode,
+    routing::{get, patch, post},
+    Router,
+};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization};
+
+use crate::{
+    responses::api_error::ApiError,
+    routes::{
+        cache_stats::cache_stats_route,
+        v1::{
+            audit::list_audit_events_route,
+            config::config_route,
+            links::{
+                create_link_route, create_links_route, delete_link_route, disable_link_route,
+                enable_link_route, link_info_route, link_qr_route, list_links_route,
+                quarantine_link_route, release_link_route, revert_link_route, update_link_route,
+            },
+            maintenance::{list_maintenance_jobs_route, run_maintenance_job_route},
+            strikes::{get_strikes_route, list_strikes_route, set_strikes_route},
+            tokens::{
+                create_token_route, list_tokens_route, revoke_token_by_id_route, revoke_token_route,
+            },
+        },
+    },
+    service_config::ServiceConfig,
+    ServiceState,
+};
+
+pub mod audit;
+pub mod config;
+pub mod extract;
+pub mod links;
+pub mod maintenance;
+pub mod strikes;
+pub mod tokens;
+
+/// Versioned API with JSON request and response bodies
+pub fn create_router(config: &ServiceConfig) -> Router<ServiceState> {
+    let mut router = Router::new()
+        .route("/links", post(create_link_route))
+        .route("/links/bulk", post(create_links_route))
+        .route("/links/{id}", get(link_info_route))
+        .route("/links/{id}/qr", get(link_qr_route))
+        .route("/config", get(config_route))
+        .route("/cache", get(cache_stats_route));
+
+    if config.token_config.is_some() {
+        router = router
+            .route("/links", get(list_links_route))
+            .route(
+                "/links/{id}",
+                patch(update_link_route).delete(delete_link_route),
+            )
+            .route("/links/{id}/disable", post(disable_link_route))
+            .route("/links/{id}/enable", post(enable_link_route))
+            .route("/links/{id}/quarantine", post(quarantine_link_route))
+            .route("/links/{id}/release", post(release_link_route))
+            .route(
+                "/links/{id}/revisions/{revision}/revert",
+                post(revert_link_route),
+            )
+            .route("/tokens", get(list_tokens_route).post(create_token_route))
+            .route("/tokens/revoke", post(revoke_token_route))
+            .route("/tokens/{id}/revoke", post(revoke_token_by_id_route))
+            .route("/strikes", get(list_strikes_route))
+            .route(
+                "/strikes/{ip}",
+                get(get_strikes_route).put(set_strikes_route),
+            )
+            .route("/audit", get(list_audit_events_route))
+            .route("/maintenance", get(list_maintenance_jobs_route))
+            .route("/maintenance/{job}/run", post(run_maintenance_job_route));
+    }
+
+    router
+}
+
+/// Token of an endpoint that can't be used without one
+fn required_token(
+    auth_header: &Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<&str, ApiError> {
+    auth_header
+        .as_ref()
+        .map(|auth| auth.token())
+        .ok_or(ApiError(StatusCode::UNAUTHORIZED))
+}
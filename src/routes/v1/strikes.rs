@@ -0,0 +1,57 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, State},
+    Json,
+};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization};
+
+use super::{
+    extract::{ApiJson, ApiPath, ApiQuery},
+    required_token,
+};
+use crate::{
+    json_schemas::{
+        list_strikes_params::ListStrikesParams, set_strikes_request::SetStrikesRequest,
+    },
+    responses::{
+        api_error::ApiError,
+        strikes::{Strikes, StrikesList},
+    },
+    service::strikes::{get_strikes, list_strikes, set_strikes},
+    ServiceState,
+};
+
+pub async fn get_strikes_route(
+    State(state): State<ServiceState>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(ip): ApiPath<IpAddr>,
+) -> Result<Json<Strikes>, ApiError> {
+    let token = required_token(&auth_header)?;
+    Ok(Json(Strikes::new(
+        ip,
+        get_strikes(&state, token, ip).await?,
+    )))
+}
+
+pub async fn set_strikes_route(
+    State(state): State<ServiceState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(ip): ApiPath<IpAddr>,
+    ApiJson(SetStrikesRequest { amount }): ApiJson<SetStrikesRequest>,
+) -> Result<Json<Strikes>, ApiError> {
+    let token = required_token(&auth_header)?;
+    let status = set_strikes(&state, addr.ip(), token, ip, amount).await?;
+    Ok(Json(Strikes::new(ip, status)))
+}
+
+pub async fn list_strikes_route(
+    State(state): State<ServiceState>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiQuery(params): ApiQuery<ListStrikesParams>,
+) -> Result<Json<StrikesList>, ApiError> {
+    let token = required_token(&auth_header)?;
+    Ok(Json(list_strikes(&state, token, params).await?))
+}
@@ -0,0 +1,70 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::TypedHeader;
+use headers::{authorization::Bearer, Authorization};
+
+use super::{
+    extract::{ApiJson, ApiPath, ApiQuery},
+    required_token,
+};
+use crate::{
+    json_schemas::{
+        create_token_params::CreateTokenParams, list_tokens_params::ListTokensParams,
+        revoke_token_request::RevokeTokenRequest,
+    },
+    responses::{
+        api_error::ApiError,
+        token_created::{TokenCreated, TokenCreatedJson},
+        token_list::TokenList,
+    },
+    service::tokens::{create_token, list_tokens, revoke_token, revoke_token_by_id},
+    ServiceState,
+};
+
+pub async fn create_token_route(
+    State(state): State<ServiceState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiJson(params): ApiJson<CreateTokenParams>,
+) -> Result<TokenCreatedJson, ApiError> {
+    let token = required_token(&auth_header)?;
+    Ok(TokenCreatedJson(TokenCreated {
+        token: create_token(&state, addr.ip(), token, params).await?,
+    }))
+}
+
+pub async fn revoke_token_route(
+    State(state): State<ServiceState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiJson(RevokeTokenRequest { token: revoked }): ApiJson<RevokeTokenRequest>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    revoke_token(&state, addr.ip(), token, &revoked).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_tokens_route(
+    State(state): State<ServiceState>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiQuery(params): ApiQuery<ListTokensParams>,
+) -> Result<Json<TokenList>, ApiError> {
+    let token = required_token(&auth_header)?;
+    Ok(Json(list_tokens(&state, token, params).await?))
+}
+
+pub async fn revoke_token_by_id_route(
+    State(state): State<ServiceState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    auth_header: Option<TypedHeader<Authorization<Bearer>>>,
+    ApiPath(id): ApiPath<u64>,
+) -> Result<StatusCode, ApiError> {
+    let token = required_token(&auth_header)?;
+    revoke_token_by_id(&state, addr.ip(), token, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,18 @@
+use std::error::Error;
+
+/// Reads a secret from the file at `{name}_FILE`, like Docker and Kubernetes secrets, or from the
+/// `{name}` environment variable. Files keep the secret out of the environment of the process,
+/// their trailing newline is ignored. `None` if neither is set.
+pub fn read_secret(name: &str) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+    let file_var = format!("{name}_FILE");
+    match (dotenvy::var(&file_var), dotenvy::var(name)) {
+        (Ok(_), Ok(_)) => Err(format!("Only one of {name} and {file_var} can be set").into()),
+        (Ok(path), Err(_)) => {
+            let secret = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {file_var} `{path}`: {e}"))?;
+            Ok(Some(secret.trim_end_matches(['\r', '\n']).to_owned()))
+        }
+        (Err(_), Ok(secret)) => Ok(Some(secret)),
+        (Err(_), Err(_)) => Ok(None),
+    }
+}
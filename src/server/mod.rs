@@ -0,0 +1,128 @@
+use std::{
+    fs::{self, Permissions},
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    os::unix::fs::{FileTypeExt, PermissionsExt},
+    path::Path,
+};
+
+use axum::serve::Listener;
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, UnixListener},
+};
+
+use crate::service_config::server::{ListenAddress, ServerConfig};
+
+use self::tls::{TlsAcceptorHandle, TlsListener};
+
+pub mod tls;
+
+/// Client address of requests over Unix domain sockets, which don't have one. Strikes and
+/// recorded IPs of those requests are all attributed to it.
+pub const UNIX_CLIENT_ADDR: SocketAddr =
+    SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+
+/// Stream of an accepted connection
+pub trait Connection: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + 'static> Connection for T {}
+
+/// Listener on one of the configured addresses
+pub enum ServiceListener {
+    Tcp(TcpListener),
+    Tls(TlsListener),
+    Unix(UnixListener),
+}
+
+impl Listener for ServiceListener {
+    type Io = Box<dyn Connection>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, addr) = Listener::accept(listener).await;
+                (Box::new(stream), addr)
+            }
+            Self::Tls(listener) => {
+                let (stream, addr) = listener.accept().await;
+                (Box::new(stream), addr)
+            }
+            Self::Unix(listener) => {
+                let (stream, _) = Listener::accept(listener).await;
+                (Box::new(stream), UNIX_CLIENT_ADDR)
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        match self {
+            Self::Tcp(listener) => listener.local_addr(),
+            Self::Tls(listener) => Ok(listener.local_addr()),
+            Self::Unix(_) => Ok(UNIX_CLIENT_ADDR),
+        }
+    }
+}
+
+/// Binds all addresses of the config, with TLS on the TCP addresses if it's configured
+pub async fn bind(config: &ServerConfig) -> Result<Vec<(ListenAddress, ServiceListener)>, String> {
+    let tls = match &config.tls {
+        Some(tls) => Some(TlsAcceptorHandle::load(tls).map_err(|e| format!("TLS: {e}"))?),
+        None => None,
+    };
+
+    let mut listeners = Vec::new();
+    for address in config.listen_addresses()? {
+        let listener = match &address {
+            ListenAddress::Tcp(addr) => {
+                let listener =
+                    bind_tcp(*addr).map_err(|e| format!("Failed to listen on {address}: {e}"))?;
+                match &tls {
+                    Some(tls) => ServiceListener::Tls(TlsListener::new(listener, tls.clone())),
+                    None => ServiceListener::Tcp(listener),
+                }
+            }
+            ListenAddress::Unix(path) => ServiceListener::Unix(
+                bind_unix(path, config.unix_socket_mode)
+                    .map_err(|e| format!("Failed to listen on {address}: {e}"))?,
+            ),
+        };
+        listeners.push((address, listener));
+    }
+    if let Some(tls) = tls {
+        tokio::spawn(tls.watch());
+    }
+    Ok(listeners)
+}
+
+/// Binds the address like `TcpListener::bind`, except that IPv6 addresses only accept IPv6
+/// connections, so that `[::]` and `0.0.0.0` can listen on the same port
+fn bind_tcp(addr: SocketAddr) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    TcpListener::from_std(socket.into())
+}
+
+/// Binds the socket, replacing a socket left behind by a previous run
+fn bind_unix(path: &Path, mode: u32) -> io::Result<UnixListener> {
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "path exists and isn't a socket",
+            ));
+        }
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, Permissions::from_mode(mode))?;
+    Ok(listener)
+}
@@ -0,0 +1,184 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use axum::serve::Listener;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::{
+    rustls::{self, Certificate, PrivateKey},
+    server::TlsStream,
+    TlsAcceptor,
+};
+
+use crate::service_config::server::TlsConfig;
+
+/// How often the certificate and key files are checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
+/// Connections that don't finish the handshake in time are dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Handshaken connections waiting to be served
+const ACCEPT_QUEUE: usize = 64;
+
+/// Current acceptor of the TLS listeners, replaced when the certificate or key changes
+#[derive(Clone)]
+pub struct TlsAcceptorHandle {
+    config: TlsConfig,
+    acceptor: Arc<RwLock<TlsAcceptor>>,
+}
+
+impl TlsAcceptorHandle {
+    pub fn load(config: &TlsConfig) -> Result<Self, String> {
+        Ok(Self {
+            acceptor: Arc::new(RwLock::new(load_acceptor(config)?)),
+            config: config.clone(),
+        })
+    }
+
+    fn current(&self) -> TlsAcceptor {
+        self.acceptor
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Reloads the certificate and key when their files change. Invalid ones are logged and the
+    /// current ones are kept.
+    pub async fn watch(self) {
+        let modified_at = || {
+            [&self.config.cert, &self.config.key].map(|path| {
+                path.metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+        };
+        let mut modified: [Option<SystemTime>; 2] = modified_at();
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let files_modified = modified_at();
+            if files_modified == modified {
+                continue;
+            }
+            modified = files_modified;
+
+            match load_acceptor(&self.config) {
+                Ok(acceptor) => {
+                    *self
+                        .acceptor
+                        .write()
+                        .unwrap_or_else(PoisonError::into_inner) = acceptor;
+                    log::info!("Reloaded TLS certificate");
+                }
+                Err(why) => {
+                    log::error!("TLS certificate reload failed, keeping the current one: {why}")
+                }
+            }
+        }
+    }
+}
+
+fn load_acceptor(config: &TlsConfig) -> Result<TlsAcceptor, String> {
+    let certs = rustls_pemfile::certs(&mut open(&config.cert)?).map_err(|e| {
+        format!(
+            "Failed to read certificates from `{}`: {e}",
+            config.cert.display()
+        )
+    })?;
+    if certs.is_empty() {
+        return Err(format!("No certificates in `{}`", config.cert.display()));
+    }
+    let key = read_key(&config.key)?;
+
+    let mut server_config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs.into_iter().map(Certificate).collect(), key)
+        .map_err(|e| format!("Invalid certificate or key: {e}"))?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, String> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| format!("Failed to open `{}`: {e}", path.display()))
+}
+
+/// First private key of the file, in PKCS #8, PKCS #1 or SEC1 format
+fn read_key(path: &Path) -> Result<PrivateKey, String> {
+    let mut reader = open(path)?;
+    loop {
+        match rustls_pemfile::read_one(&mut reader) {
+            Ok(Some(
+                rustls_pemfile::Item::PKCS8Key(key)
+                | rustls_pemfile::Item::RSAKey(key)
+                | rustls_pemfile::Item::ECKey(key),
+            )) => return Ok(PrivateKey(key)),
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(format!("No private key in `{}`", path.display())),
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read private key from `{}`: {e}",
+                    path.display()
+                ))
+            }
+        }
+    }
+}
+
+/// TCP listener that serves TLS. Handshakes run in their own tasks, so slow clients don't hold
+/// up others.
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    pub fn new(mut listener: TcpListener, acceptor: TlsAcceptorHandle) -> Self {
+        let local_addr = listener
+            .local_addr()
+            .unwrap_or_else(|_| SocketAddr::from(([0, 0, 0, 0], 0)));
+        let (sender, connections) = mpsc::channel(ACCEPT_QUEUE);
+        tokio::spawn(async move {
+            while !sender.is_closed() {
+                let (stream, addr) = Listener::accept(&mut listener).await;
+                let acceptor = acceptor.current();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            // The listener is gone if sending fails
+                            let _ = sender.send((stream, addr)).await;
+                        }
+                        Ok(Err(e)) => log::debug!("TLS handshake with {addr} failed: {e}"),
+                        Err(_) => log::debug!("TLS handshake with {addr} timed out"),
+                    }
+                });
+            }
+        });
+        Self {
+            local_addr,
+            connections,
+        }
+    }
+
+    pub async fn accept(&mut self) -> (TlsStream<TcpStream>, SocketAddr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // The accepting task only stops once the listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
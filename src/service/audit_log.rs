@@ -0,0 +1,133 @@
+use std::net::IpAddr;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+
+use super::identify;
+use crate::{
+    json_schemas::list_audit_events_params::ListAuditEventsParams,
+    responses::audit_events::{AuditEvent, AuditEventList},
+    ServiceState,
+};
+
+const MAX_LIST_LIMIT: u32 = 500;
+
+#[derive(Debug)]
+struct AuditEventQuery {
+    id: u64,
+    created_at: DateTime<Utc>,
+    actor_token: Option<u64>,
+    action: String,
+    target: String,
+    target_domain: Option<String>,
+    client_ip: Option<Vec<u8>>,
+}
+
+/// Lists audit events, newest first. Requires admin permission, client IPs are only shown to
+/// tokens that can view IPs.
+#[tracing::instrument(skip_all)]
+pub async fn list_audit_events(
+    state: &ServiceState,
+    token: &str,
+    params: ListAuditEventsParams,
+) -> Result<AuditEventList, StatusCode> {
+    let identity = identify(state, token).await?;
+    if !identity.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // `Some(None)` filters by the master token
+    let actor = params
+        .actor
+        .as_deref()
+        .map(|actor| match actor {
+            "master" => Ok(None),
+            id => id
+                .parse::<u64>()
+                .map(Some)
+                .map_err(|_| StatusCode::BAD_REQUEST),
+        })
+        .transpose()?;
+    let action = params.action.map(|action| action.as_str());
+
+    let limit = params.limit.clamp(1, MAX_LIST_LIMIT);
+    // One extra row tells whether there is a next page
+    let fetch_limit = limit + 1;
+
+    let rows = sqlx::query_as!(
+        AuditEventQuery,
+        r#"
+        SELECT id, created_at, actor_token, action, target, target_domain, client_ip
+        FROM audit_events
+        WHERE (? IS NULL OR id < ?)
+        AND (? = FALSE OR actor_token <=> ?)
+        AND (? IS NULL OR action = ?)
+        AND (? IS NULL OR target = ?)
+        AND (? IS NULL OR target_domain = ?)
+        AND (? IS NULL OR created_at >= ?)
+        AND (? IS NULL OR created_at < ?)
+        ORDER BY id DESC
+        LIMIT ?
+        "#,
+        params.cursor,
+        params.cursor,
+        actor.is_some(),
+        actor.flatten(),
+        action,
+        action,
+        params.target,
+        params.target,
+        params.domain,
+        params.domain,
+        params.created_after,
+        params.created_after,
+        params.created_before,
+        params.created_before,
+        fetch_limit
+    )
+    .fetch_all(state.db.as_ref())
+    .await
+    .map_err(|e| {
+        log::error!("Error listing audit events: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut events = rows
+        .into_iter()
+        .map(|row| {
+            let client_ip = match row.client_ip {
+                Some(bytes) if identity.can_view_ips() => {
+                    Some(bincode::deserialize::<IpAddr>(&bytes).map_err(|e| {
+                        log::error!("Error deserializing client ip of audit event: {e}");
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    })?)
+                }
+                _ => None,
+            };
+            Ok(AuditEvent {
+                id: row.id,
+                created_at: row.created_at,
+                actor: row.actor_token.into(),
+                action: row.action.parse().map_err(|e| {
+                    log::error!("Error reading audit event {}: {e}", row.id);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?,
+                target: row.target,
+                domain: row.target_domain,
+                client_ip,
+            })
+        })
+        .collect::<Result<Vec<_>, StatusCode>>()?;
+
+    let next_cursor = if events.len() > limit as usize {
+        events.truncate(limit as usize);
+        events.last().map(|last| last.id)
+    } else {
+        None
+    };
+
+    Ok(AuditEventList {
+        events,
+        next_cursor,
+    })
+}
@@ -0,0 +1,237 @@
+use axum::http::StatusCode;
+use blake3::Hash;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    json_schemas::token_permissions::TokenPermissions,
+    links::is_deleted,
+    responses::link_info::{LinkInfo, LinkRevision},
+    service_config::domains::DomainConfig,
+    tokens::{check_permission, identify_token},
+    ServiceState,
+};
+
+#[derive(Debug)]
+struct LinkInfoQuery {
+    id: String,
+    hash: Option<Vec<u8>>,
+    link: String,
+    created_at: DateTime<Utc>,
+    editable: bool,
+    disabled_at: Option<DateTime<Utc>>,
+    quarantined_at: Option<DateTime<Utc>>,
+    created_by_token: Option<u64>,
+    password_protected: bool,
+    redirect_code: Option<u16>,
+    forward_query: bool,
+    forward_path: bool,
+    cache_max_age: Option<u32>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+struct RevisionQuery {
+    id: u64,
+    previous_link: String,
+    link: String,
+    created_at: DateTime<Utc>,
+    actor_token: Option<u64>,
+}
+
+#[derive(Debug)]
+struct CreatedByQuery {
+    created_by: Vec<u8>,
+}
+
+/// Information about the link. Origin IP is only included for tokens with IP view permission, the
+/// destination of a password-protected link only for its owner and admins, and of a quarantined
+/// link only for admins.
+#[tracing::instrument(skip_all)]
+pub async fn link_info(
+    ServiceState { db, config, .. }: &ServiceState,
+    domain: &DomainConfig,
+    token: Option<&str>,
+    id: &str,
+) -> Result<LinkInfo, StatusCode> {
+    let Some(LinkInfoQuery {
+        id,
+        hash,
+        link,
+        created_at,
+        editable,
+        disabled_at,
+        quarantined_at,
+        created_by_token,
+        password_protected,
+        redirect_code,
+        forward_query,
+        forward_path,
+        cache_max_age,
+        expires_at,
+    }) = sqlx::query_as!(
+        LinkInfoQuery,
+        r#"
+        SELECT
+            id,
+            hash,
+            link,
+            created_at,
+            editable AS `editable: _`,
+            disabled_at,
+            quarantined_at,
+            created_by_token,
+            password_hash IS NOT NULL AS `password_protected: _`,
+            redirect_code,
+            forward_query AS `forward_query: _`,
+            forward_path AS `forward_path: _`,
+            cache_max_age,
+            expires_at
+        FROM links
+        WHERE domain = ? AND id = ?
+        "#,
+        domain.namespace(),
+        id
+    )
+    .fetch_optional(db.as_ref())
+    .await
+    .map_err(|e| {
+        log::error!("Error looking up link: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    else {
+        return Err(if is_deleted(db.as_ref(), domain.namespace(), id).await? {
+            StatusCode::GONE
+        } else {
+            StatusCode::NOT_FOUND
+        });
+    };
+
+    log::debug!("Received link info: id {id}, hash {hash:?}, link {link}, created_at {created_at}");
+
+    let config = config.load();
+
+    let has_ip_view_perm = match token {
+        None => false,
+        Some(tok) => {
+            if let Some(tok_config) = &config.token_config {
+                check_permission(
+                    db.as_ref(),
+                    &tok_config.master_tokens,
+                    tok,
+                    TokenPermissions::new().view_ips(),
+                )
+                .await?
+            } else {
+                false
+            }
+        }
+    };
+
+    // Destination of a quarantined link is only shown to admins, of a password-protected link
+    // also to its owner
+    let quarantined = quarantined_at.is_some();
+    let show_destination = if !password_protected && !quarantined {
+        true
+    } else if let (Some(tok), Some(tok_config)) = (token, &config.token_config) {
+        let identity = identify_token(db.as_ref(), &tok_config.master_tokens, tok).await?;
+        let is_owner = identity.id().is_some() && identity.id() == created_by_token;
+        identity.is_admin() || (is_owner && !quarantined)
+    } else {
+        false
+    };
+
+    let created_by = if has_ip_view_perm {
+        let created_by = sqlx::query_as!(
+            CreatedByQuery,
+            "SELECT created_by FROM origins WHERE domain = ? AND id = ?",
+            domain.namespace(),
+            &id
+        )
+        .fetch_optional(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Error looking up link `{id}` origin: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        if let Some(CreatedByQuery { created_by: bytes }) = created_by {
+            Some(bincode::deserialize(&bytes).map_err(|e| {
+                log::error!("Error deserializing origin ip: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    let revisions = if !show_destination {
+        Vec::new()
+    } else {
+        sqlx::query_as!(
+            RevisionQuery,
+            r#"
+        SELECT id, previous_link, link, created_at, actor_token
+        FROM link_revisions
+        WHERE link_domain = ? AND link_id = ?
+        ORDER BY id
+        "#,
+            domain.namespace(),
+            &id
+        )
+        .fetch_all(db.as_ref())
+        .await
+        .map_err(|e| {
+            log::error!("Error looking up link `{id}` revisions: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .map(
+            |RevisionQuery {
+                 id,
+                 previous_link,
+                 link,
+                 created_at,
+                 actor_token,
+             }| LinkRevision {
+                id,
+                previous_link,
+                link,
+                changed_at: created_at,
+                changed_by: actor_token.into(),
+            },
+        )
+        .collect()
+    };
+
+    Ok(LinkInfo {
+        id,
+        hash: hash
+            .filter(|_| show_destination)
+            .map(|hash| {
+                hash.try_into()
+                    .map(<Hash as From<[u8; 32]>>::from)
+                    .map_err(|e: Vec<u8>| {
+                        log::error!(
+                            "Error converting hash from blob: blob length is {}",
+                            e.len()
+                        );
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    })
+            })
+            .transpose()?,
+        link: show_destination.then_some(link),
+        created_at,
+        created_by,
+        editable,
+        password_protected,
+        disabled_at,
+        quarantined_at,
+        redirect_code,
+        forward_query,
+        forward_path,
+        cache_max_age,
+        expires_at,
+        revisions,
+    })
+}
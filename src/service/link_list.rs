@@ -0,0 +1,186 @@
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+
+use super::identify;
+use crate::{
+    json_schemas::list_links_params::{ListLinksParams, SortOrder},
+    responses::link_list::{LinkList, LinkListEntry},
+    service_config::domains::DomainConfig,
+    tokens::TokenIdentity,
+    ServiceState,
+};
+
+const MAX_LIST_LIMIT: u32 = 500;
+
+#[derive(Debug)]
+struct LinkListQuery {
+    id: String,
+    link: String,
+    created_at: DateTime<Utc>,
+    created_by_token: Option<u64>,
+}
+
+/// Lists links on the domain, by default only those owned by the token
+#[tracing::instrument(skip_all)]
+pub async fn list_links(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    token: &str,
+    params: ListLinksParams,
+) -> Result<LinkList, StatusCode> {
+    let identity = identify(state, token).await?;
+    let db = &state.db;
+
+    let owner = if params.all {
+        if !identity.is_admin() {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        None
+    } else if let Some(owner) = params.owner {
+        if identity.id() != Some(owner) && !identity.is_admin() {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        Some(owner)
+    } else {
+        match identity {
+            // Master token doesn't own any links, so it sees all of them
+            TokenIdentity::Master => None,
+            TokenIdentity::Token { id, .. } => Some(id),
+        }
+    };
+
+    let (cursor_created_at, cursor_id) = match &params.cursor {
+        Some(cursor) => {
+            let (created_at, id) = parse_cursor(cursor).ok_or(StatusCode::BAD_REQUEST)?;
+            (Some(created_at), Some(id))
+        }
+        None => (None, None),
+    };
+
+    let domain_pattern = params.domain.as_deref().map(domain_pattern).transpose()?;
+
+    let limit = params.limit.clamp(1, MAX_LIST_LIMIT);
+    // One extra row tells whether there is a next page
+    let fetch_limit = limit + 1;
+
+    let rows = match params.order {
+        SortOrder::Asc => {
+            sqlx::query_as!(
+                LinkListQuery,
+                r#"
+            SELECT id, link, created_at, created_by_token
+            FROM links
+            WHERE domain = ?
+            AND (? IS NULL OR created_by_token = ?)
+            AND (? IS NULL OR created_at >= ?)
+            AND (? IS NULL OR created_at < ?)
+            AND (? IS NULL OR link REGEXP ?)
+            AND (? IS NULL OR (created_at, id) > (?, ?))
+            ORDER BY created_at ASC, id ASC
+            LIMIT ?
+            "#,
+                domain.namespace(),
+                owner,
+                owner,
+                params.created_after,
+                params.created_after,
+                params.created_before,
+                params.created_before,
+                domain_pattern,
+                domain_pattern,
+                cursor_created_at,
+                cursor_created_at,
+                cursor_id,
+                fetch_limit
+            )
+            .fetch_all(db.as_ref())
+            .await
+        }
+        SortOrder::Desc => {
+            sqlx::query_as!(
+                LinkListQuery,
+                r#"
+            SELECT id, link, created_at, created_by_token
+            FROM links
+            WHERE domain = ?
+            AND (? IS NULL OR created_by_token = ?)
+            AND (? IS NULL OR created_at >= ?)
+            AND (? IS NULL OR created_at < ?)
+            AND (? IS NULL OR link REGEXP ?)
+            AND (? IS NULL OR (created_at, id) < (?, ?))
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            "#,
+                domain.namespace(),
+                owner,
+                owner,
+                params.created_after,
+                params.created_after,
+                params.created_before,
+                params.created_before,
+                domain_pattern,
+                domain_pattern,
+                cursor_created_at,
+                cursor_created_at,
+                cursor_id,
+                fetch_limit
+            )
+            .fetch_all(db.as_ref())
+            .await
+        }
+    }
+    .map_err(|e| {
+        log::error!("Error listing links: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut links: Vec<LinkListEntry> = rows
+        .into_iter()
+        .map(
+            |LinkListQuery {
+                 id,
+                 link,
+                 created_at,
+                 created_by_token,
+             }| LinkListEntry {
+                id,
+                link,
+                created_at,
+                owner: created_by_token,
+            },
+        )
+        .collect();
+
+    let next_cursor = if links.len() > limit as usize {
+        links.truncate(limit as usize);
+        links
+            .last()
+            .map(|last| format!("{}_{}", last.created_at.timestamp(), last.id))
+    } else {
+        None
+    };
+
+    Ok(LinkList { links, next_cursor })
+}
+
+/// Cursor is the creation timestamp and id of the last link on the previous page
+fn parse_cursor(cursor: &str) -> Option<(DateTime<Utc>, String)> {
+    let (timestamp, id) = cursor.split_once('_')?;
+    let created_at = DateTime::from_timestamp(timestamp.parse().ok()?, 0)?;
+    Some((created_at, id.to_string()))
+}
+
+/// Regular expression matching links that point to the domain
+fn domain_pattern(domain: &str) -> Result<String, StatusCode> {
+    if domain.is_empty()
+        || !domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(format!(
+        "^[a-z][a-z0-9+.-]*://([^/@]*@)?{}([:/?#]|$)",
+        domain.replace('.', "[.]")
+    ))
+}
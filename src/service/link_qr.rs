@@ -0,0 +1,99 @@
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use headers::{ETag, IfNoneMatch};
+
+use crate::{
+    json_schemas::qr_params::{QrFormat, QrParams},
+    links::{is_deleted, qr::render_qr},
+    responses::qr_code::QrCodeResponse,
+    service_config::domains::DomainConfig,
+    ServiceState,
+};
+
+#[derive(Debug)]
+struct LinkStatusQuery {
+    created_at: DateTime<Utc>,
+    disabled_at: Option<DateTime<Utc>>,
+    quarantined_at: Option<DateTime<Utc>>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// QR code of the short link, made absolute with the origin of the request if the base URL isn't
+/// absolute. Only active links have one, disabled, quarantined and expired links are gone like
+/// deleted ones. Returns 400 if the origin is needed but invalid.
+pub async fn link_qr_code(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    origin: Option<&str>,
+    id: &str,
+    format: QrFormat,
+    params: QrParams,
+    if_none_match: Option<&IfNoneMatch>,
+) -> Result<QrCodeResponse, StatusCode> {
+    let Some(status) = sqlx::query_as!(
+        LinkStatusQuery,
+        r#"
+        SELECT created_at, disabled_at, quarantined_at, expires_at
+        FROM links
+        WHERE domain = ? AND id = ?
+        "#,
+        domain.namespace(),
+        id
+    )
+    .fetch_optional(state.db.as_ref())
+    .await
+    .map_err(|e| {
+        log::error!("Error looking up link `{id}` for QR code: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    else {
+        return Err(
+            if is_deleted(state.db.as_ref(), domain.namespace(), id).await? {
+                StatusCode::GONE
+            } else {
+                StatusCode::NOT_FOUND
+            },
+        );
+    };
+    // Expired links are only deleted once the `expired_links` job runs
+    let expired = status
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now());
+    if status.disabled_at.is_some() || status.quarantined_at.is_some() || expired {
+        return Err(StatusCode::GONE);
+    }
+
+    let mut location = domain.link_location(&state.config.load().routes, id);
+    if location.starts_with('/') {
+        location.insert_str(0, origin.ok_or(StatusCode::BAD_REQUEST)?);
+    }
+
+    // The creation time tells the link apart from earlier ones with the same id
+    let etag = format!(
+        "\"{}\"",
+        &blake3::hash(
+            format!(
+                "{location}|{}|{format:?}|{params:?}",
+                status.created_at.timestamp_micros()
+            )
+            .as_bytes()
+        )
+        .to_hex()[..32]
+    );
+    let not_modified = if_none_match.is_some_and(|if_none_match| {
+        etag.parse::<ETag>()
+            .is_ok_and(|etag| !if_none_match.precondition_passes(&etag))
+    });
+    if not_modified {
+        return Ok(QrCodeResponse::NotModified { etag });
+    }
+
+    // Rendering large PNGs takes a while, keep it off the async workers
+    let body = tokio::task::spawn_blocking(move || render_qr(&location, format, &params))
+        .await
+        .map_err(|e| {
+            log::error!("Error rendering QR code: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })??;
+    Ok(QrCodeResponse::Image { body, format, etag })
+}
@@ -0,0 +1,360 @@
+use std::net::IpAddr;
+
+use axum::http::StatusCode;
+use sqlx::Connection;
+
+use super::{audit, identify};
+use crate::{
+    audit::{AuditAction, AuditTarget},
+    json_schemas::create_link_params::CreateLinkParams,
+    links::{
+        self, check_creation_auth, check_link_params, check_strikes, link_origin, management,
+        parse_link, password::hash_password,
+    },
+    responses::{
+        bulk_created_links::{BulkLinkOutcome, BulkLinkResult},
+        created_link::CreatedLink,
+        link_creation_error::LinkCreationError,
+    },
+    service_config::domains::DomainConfig,
+    ServiceState,
+};
+
+/// Who is creating links, checked before any link is created
+struct LinkCreator {
+    created_by: Option<Vec<u8>>,
+    owner: Option<u64>,
+}
+
+async fn check_creator(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: Option<&str>,
+    params: &CreateLinkParams,
+) -> Result<LinkCreator, LinkCreationError> {
+    let ServiceState { db, config, .. } = state;
+    let config = &config.load();
+    let creator = check_creation_auth(db.as_ref(), config, domain, token).await?;
+    check_link_params(params, creator.as_ref())?;
+
+    check_strikes(db.as_ref(), config, ip).await?;
+
+    Ok(LinkCreator {
+        created_by: link_origin(config, ip)?,
+        owner: creator.and_then(|creator| creator.id()),
+    })
+}
+
+/// Shortens a link on the domain. `password` protects the link, if supplied.
+pub async fn create_link(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: Option<&str>,
+    mut params: CreateLinkParams,
+    password: Option<String>,
+    url: &str,
+) -> Result<CreatedLink, LinkCreationError> {
+    let LinkCreator { created_by, owner } =
+        check_creator(state, domain, ip, token, &params).await?;
+
+    let uri = parse_link(url).ok_or(StatusCode::BAD_REQUEST)?;
+    if let Some(password) = password {
+        params.password_hash = Some(hash_password(password).await?);
+    }
+
+    let mut conn = state.db.acquire().await.map_err(|e| {
+        log::error!("Error acquiring database connection: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let id = links::create_link(
+        &mut conn,
+        domain,
+        &uri,
+        created_by.as_deref(),
+        owner,
+        &params,
+    )
+    .await?;
+    // The new id might have been cached as missing
+    state.link_cache.invalidate(domain.namespace(), &id);
+
+    Ok(CreatedLink {
+        location: domain.link_location(&state.config.load().routes, &id),
+        id,
+    })
+}
+
+/// Shortens up to `max_bulk_links` links in a single transaction. Invalid links and links that
+/// couldn't be stored are reported in the results instead of failing the whole request.
+pub async fn create_links(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: Option<&str>,
+    mut params: CreateLinkParams,
+    password: Option<String>,
+    urls: Vec<String>,
+) -> Result<Vec<BulkLinkResult>, LinkCreationError> {
+    let LinkCreator { created_by, owner } =
+        check_creator(state, domain, ip, token, &params).await?;
+
+    if urls.is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    if urls.len() > usize::from(state.config.load().max_bulk_links) {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE.into());
+    }
+    if let Some(password) = password {
+        params.password_hash = Some(hash_password(password).await?);
+    }
+
+    let mut tx = state.db.begin().await.map_err(|e| {
+        log::error!("Error starting bulk link creation transaction: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut results = Vec::with_capacity(urls.len());
+    for url in urls {
+        let Some(uri) = parse_link(&url) else {
+            results.push(BulkLinkResult {
+                link: url,
+                outcome: BulkLinkOutcome::Error("Invalid link".into()),
+            });
+            continue;
+        };
+        // Each link gets a savepoint, so a failed one is reported without undoing the others
+        let mut savepoint = Connection::begin(&mut *tx).await.map_err(|e| {
+            log::error!("Error starting savepoint of bulk link creation: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let outcome = match links::create_link(
+            &mut savepoint,
+            domain,
+            &uri,
+            created_by.as_deref(),
+            owner,
+            &params,
+        )
+        .await
+        {
+            Ok(id) => {
+                savepoint.commit().await.map_err(|e| {
+                    log::error!("Error releasing savepoint of bulk link creation: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+                BulkLinkOutcome::Id(id)
+            }
+            Err(status) => {
+                savepoint.rollback().await.map_err(|e| {
+                    log::error!("Error rolling back savepoint of bulk link creation: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+                BulkLinkOutcome::Error(
+                    status
+                        .canonical_reason()
+                        .unwrap_or("Link creation failed")
+                        .to_owned(),
+                )
+            }
+        };
+        results.push(BulkLinkResult { link: url, outcome });
+    }
+
+    tx.commit().await.map_err(|e| {
+        log::error!("Error committing bulk link creation transaction: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // New ids might have been cached as missing
+    for result in &results {
+        if let BulkLinkOutcome::Id(id) = &result.outcome {
+            state.link_cache.invalidate(domain.namespace(), id);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Changes the destination of an editable link
+pub async fn update_link(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: &str,
+    id: &str,
+    url: &str,
+) -> Result<(), StatusCode> {
+    let actor = identify(state, token).await?;
+    let uri = parse_link(url).ok_or(StatusCode::BAD_REQUEST)?;
+
+    management::change_link_destination(state.db.as_ref(), domain.namespace(), id, &uri, actor)
+        .await?;
+    state.link_cache.invalidate(domain.namespace(), id);
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::LinkUpdated,
+        AuditTarget::Link {
+            namespace: domain.namespace(),
+            id,
+        },
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn revert_link(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: &str,
+    id: &str,
+    revision: u64,
+) -> Result<(), StatusCode> {
+    let actor = identify(state, token).await?;
+
+    management::revert_link_revision(state.db.as_ref(), domain.namespace(), id, revision, actor)
+        .await?;
+    state.link_cache.invalidate(domain.namespace(), id);
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::LinkReverted,
+        AuditTarget::Link {
+            namespace: domain.namespace(),
+            id,
+        },
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn delete_link(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: &str,
+    id: &str,
+) -> Result<(), StatusCode> {
+    let actor = identify(state, token).await?;
+
+    management::delete_link(state.db.as_ref(), domain.namespace(), id, actor).await?;
+    state.link_cache.invalidate(domain.namespace(), id);
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::LinkDeleted,
+        AuditTarget::Link {
+            namespace: domain.namespace(),
+            id,
+        },
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn disable_link(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: &str,
+    id: &str,
+) -> Result<(), StatusCode> {
+    let actor = identify(state, token).await?;
+
+    management::disable_link(state.db.as_ref(), domain.namespace(), id, actor).await?;
+    state.link_cache.invalidate(domain.namespace(), id);
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::LinkDisabled,
+        AuditTarget::Link {
+            namespace: domain.namespace(),
+            id,
+        },
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn enable_link(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: &str,
+    id: &str,
+) -> Result<(), StatusCode> {
+    let actor = identify(state, token).await?;
+
+    management::enable_link(state.db.as_ref(), domain.namespace(), id, actor).await?;
+    state.link_cache.invalidate(domain.namespace(), id);
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::LinkEnabled,
+        AuditTarget::Link {
+            namespace: domain.namespace(),
+            id,
+        },
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn quarantine_link(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: &str,
+    id: &str,
+) -> Result<(), StatusCode> {
+    let actor = identify(state, token).await?;
+
+    management::quarantine_link(state.db.as_ref(), domain.namespace(), id, actor).await?;
+    state.link_cache.invalidate(domain.namespace(), id);
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::LinkQuarantined,
+        AuditTarget::Link {
+            namespace: domain.namespace(),
+            id,
+        },
+    )
+    .await;
+    Ok(())
+}
+
+pub async fn release_link(
+    state: &ServiceState,
+    domain: &DomainConfig,
+    ip: IpAddr,
+    token: &str,
+    id: &str,
+) -> Result<(), StatusCode> {
+    let actor = identify(state, token).await?;
+
+    management::release_link(state.db.as_ref(), domain.namespace(), id, actor).await?;
+    state.link_cache.invalidate(domain.namespace(), id);
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::LinkReleased,
+        AuditTarget::Link {
+            namespace: domain.namespace(),
+            id,
+        },
+    )
+    .await;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::net::IpAddr;
+
+use axum::http::StatusCode;
+
+use super::{audit, require_admin};
+use crate::{
+    audit::{AuditAction, AuditTarget},
+    maintenance::{run_job, MaintenanceJob},
+    responses::maintenance::{MaintenanceJobList, MaintenanceJobRun},
+    ServiceState,
+};
+
+/// Lists the maintenance jobs with their last runs. Requires admin permission.
+pub async fn list_jobs(
+    state: &ServiceState,
+    token: &str,
+) -> Result<MaintenanceJobList, StatusCode> {
+    require_admin(state, token).await?;
+    Ok(MaintenanceJobList {
+        jobs: state.maintenance.status(&state.config.load()),
+    })
+}
+
+/// Runs the job now and waits for it to finish. Requires admin permission. 404 if the job isn't
+/// available with the config, 409 if it's already running.
+pub async fn trigger_job(
+    state: &ServiceState,
+    ip: IpAddr,
+    token: &str,
+    job: MaintenanceJob,
+) -> Result<MaintenanceJobRun, StatusCode> {
+    let actor = require_admin(state, token).await?;
+    if !job.is_available(&state.config.load()) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let run = run_job(state, job, true)
+        .await
+        .ok_or(StatusCode::CONFLICT)?;
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::MaintenanceJobRun,
+        AuditTarget::Job(job.as_str()),
+    )
+    .await;
+    Ok(run)
+}
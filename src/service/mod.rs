@@ -0,0 +1,68 @@
+use std::net::IpAddr;
+
+use axum::http::StatusCode;
+
+use crate::{
+    audit::{record_event, AuditAction, AuditTarget},
+    service_config::token::MasterTokens,
+    tokens::{identify_token, TokenIdentity},
+    ServiceState,
+};
+
+pub mod audit_log;
+pub mod link_info;
+pub mod link_list;
+pub mod link_qr;
+pub mod links;
+pub mod maintenance;
+pub mod strikes;
+pub mod tokens;
+
+/// Master tokens, if the token system is enabled. Endpoints that need tokens don't exist
+/// otherwise.
+fn master_tokens(state: &ServiceState) -> Result<MasterTokens, StatusCode> {
+    state
+        .config
+        .load()
+        .token_config
+        .as_ref()
+        .map(|tok_config| tok_config.master_tokens.clone())
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn identify(state: &ServiceState, token: &str) -> Result<TokenIdentity, StatusCode> {
+    identify_token(state.db.as_ref(), &master_tokens(state)?, token).await
+}
+
+/// Who the token belongs to, 403 unless it's an admin
+async fn require_admin(state: &ServiceState, token: &str) -> Result<TokenIdentity, StatusCode> {
+    let identity = identify(state, token).await?;
+    if identity.is_admin() {
+        Ok(identity)
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+/// Who the token belongs to, 403 unless it can view IPs
+async fn require_ip_view(state: &ServiceState, token: &str) -> Result<TokenIdentity, StatusCode> {
+    let identity = identify(state, token).await?;
+    if identity.can_view_ips() {
+        Ok(identity)
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+/// Records the action in the audit log, `actor_token` is `None` for the master token. The client
+/// IP is only kept with IP recording.
+async fn audit(
+    state: &ServiceState,
+    actor_token: Option<u64>,
+    ip: IpAddr,
+    action: AuditAction,
+    target: AuditTarget<'_>,
+) {
+    let client_ip = state.config.load().ip_recording.is_some().then_some(ip);
+    record_event(state.db.as_ref(), actor_token, action, target, client_ip).await;
+}
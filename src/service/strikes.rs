@@ -0,0 +1,72 @@
+use std::net::IpAddr;
+
+use axum::http::StatusCode;
+
+use super::{audit, require_admin, require_ip_view};
+use crate::{
+    audit::{AuditAction, AuditTarget},
+    json_schemas::list_strikes_params::ListStrikesParams,
+    responses::strikes::{Strikes, StrikesList},
+    strikes::{self, StrikeStatus, StrikesPage},
+    ServiceState,
+};
+
+const MAX_LIST_LIMIT: u32 = 500;
+
+/// Strikes recorded for the IP and whether it's banned. Requires IP view permission.
+pub async fn get_strikes(
+    state: &ServiceState,
+    token: &str,
+    ip: IpAddr,
+) -> Result<StrikeStatus, StatusCode> {
+    require_ip_view(state, token).await?;
+    strikes::get_strikes(&state.db, &state.config.load(), ip).await
+}
+
+/// A page of IPs with strikes or a ban, ordered by IP, optionally only the ones containing the
+/// search text. Pages can have fewer entries than the limit, the list ends when there is no next
+/// cursor. Requires IP view permission.
+pub async fn list_strikes(
+    state: &ServiceState,
+    token: &str,
+    params: ListStrikesParams,
+) -> Result<StrikesList, StatusCode> {
+    require_ip_view(state, token).await?;
+    let limit = params.limit.clamp(1, MAX_LIST_LIMIT);
+    let StrikesPage {
+        mut strikes,
+        next_cursor,
+    } = strikes::list_strikes(&state.db, &state.config.load(), params.cursor, limit).await?;
+    if let Some(search) = params.search.filter(|search| !search.is_empty()) {
+        strikes.retain(|(ip, _)| ip.to_string().contains(&search));
+    }
+    Ok(StrikesList {
+        strikes: strikes
+            .into_iter()
+            .map(|(ip, status)| Strikes::new(ip, status))
+            .collect(),
+        next_cursor,
+    })
+}
+
+/// Replaces the amount of strikes recorded for the IP, 0 removes them along with any ban.
+/// Requires admin permission.
+pub async fn set_strikes(
+    state: &ServiceState,
+    client_ip: IpAddr,
+    token: &str,
+    ip: IpAddr,
+    amount: u16,
+) -> Result<StrikeStatus, StatusCode> {
+    let actor = require_admin(state, token).await?;
+    let status = strikes::set_strikes(&state.db, &state.config.load(), ip, amount).await?;
+    audit(
+        state,
+        actor.id(),
+        client_ip,
+        AuditAction::StrikesSet,
+        AuditTarget::Ip(ip),
+    )
+    .await;
+    Ok(status)
+}
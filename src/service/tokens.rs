@@ -0,0 +1,107 @@
+use std::net::IpAddr;
+
+use axum::http::StatusCode;
+
+use super::{audit, require_admin};
+use crate::{
+    audit::{AuditAction, AuditTarget},
+    json_schemas::{create_token_params::CreateTokenParams, list_tokens_params::ListTokensParams},
+    responses::token_list::TokenList,
+    tokens::{expire_token, expire_token_by_id, insert_token, list_tokens as query_tokens},
+    ServiceState,
+};
+
+const MAX_LIST_LIMIT: u32 = 500;
+
+/// Creates a token with the requested permissions. Requires admin permission.
+pub async fn create_token(
+    state: &ServiceState,
+    ip: IpAddr,
+    token: &str,
+    params: CreateTokenParams,
+) -> Result<String, StatusCode> {
+    let actor = require_admin(state, token).await?;
+    let (id, new_token) = insert_token(&state.db, params).await?;
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::TokenCreated,
+        AuditTarget::Token(id),
+    )
+    .await;
+    Ok(new_token)
+}
+
+/// Expires the token. Tokens can revoke themselves, revoking other tokens requires admin
+/// permission.
+pub async fn revoke_token(
+    state: &ServiceState,
+    ip: IpAddr,
+    token: &str,
+    revoked: &str,
+) -> Result<(), StatusCode> {
+    let actor = if token != revoked {
+        Some(require_admin(state, token).await?)
+    } else {
+        None
+    };
+    if let Some(id) = expire_token(&state.db, revoked).await? {
+        // A token revoking itself is the actor
+        let actor_token = actor.map_or(Some(id), |actor| actor.id());
+        audit(
+            state,
+            actor_token,
+            ip,
+            AuditAction::TokenRevoked,
+            AuditTarget::Token(id),
+        )
+        .await;
+    }
+    Ok(())
+}
+
+/// A page of stored tokens, without the tokens themselves, oldest first. Requires admin
+/// permission.
+pub async fn list_tokens(
+    state: &ServiceState,
+    token: &str,
+    params: ListTokensParams,
+) -> Result<TokenList, StatusCode> {
+    require_admin(state, token).await?;
+    let limit = params.limit.clamp(1, MAX_LIST_LIMIT);
+    // One extra row tells whether there is a next page
+    let mut tokens = query_tokens(&state.db, params.all, params.cursor, limit + 1).await?;
+    let next_cursor = if tokens.len() > limit as usize {
+        tokens.truncate(limit as usize);
+        tokens.last().map(|last| last.id)
+    } else {
+        None
+    };
+    Ok(TokenList {
+        tokens: tokens.into_iter().map(Into::into).collect(),
+        next_cursor,
+    })
+}
+
+/// Expires the token with the id, as shown in the token list. Requires admin permission.
+pub async fn revoke_token_by_id(
+    state: &ServiceState,
+    ip: IpAddr,
+    token: &str,
+    id: u64,
+) -> Result<(), StatusCode> {
+    let actor = require_admin(state, token).await?;
+    if !expire_token_by_id(&state.db, id).await? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    audit(
+        state,
+        actor.id(),
+        ip,
+        AuditAction::TokenRevoked,
+        AuditTarget::Token(id),
+    )
+    .await;
+    Ok(())
+}
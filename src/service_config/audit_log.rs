@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use chrono::Duration;
+use serde::Deserialize;
+
+use super::{duration::deserialize_period, ip_recording::deserialize_cron};
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct AuditLogConfig {
+    #[serde(
+        default = "default_retention_period",
+        deserialize_with = "deserialize_period"
+    )]
+    pub retention_period: Duration,
+    /// Cron expression with a seconds field, which is added to expressions without one
+    #[serde(
+        default = "default_check_period",
+        deserialize_with = "deserialize_cron"
+    )]
+    pub retention_check_period: Arc<str>,
+}
+
+impl Default for AuditLogConfig {
+    fn default() -> Self {
+        Self {
+            retention_period: default_retention_period(),
+            retention_check_period: default_check_period(),
+        }
+    }
+}
+
+fn default_retention_period() -> Duration {
+    Duration::days(365)
+}
+
+fn default_check_period() -> Arc<str> {
+    "0 0 0 * * *".into()
+}
@@ -0,0 +1,62 @@
+use serde::Deserialize;
+
+use super::routes::RoutesConfig;
+use crate::json_schemas::redirect_code::RedirectCode;
+
+/// Short domain that links can be created on. Links are scoped to the namespace of the domain,
+/// so the same id can point to different links on different domains.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct DomainConfig {
+    /// Host name the domain is requested with, matched against the `Host` of requests
+    pub host: String,
+    /// Namespace of link ids, the host name by default. Domains with the same namespace share
+    /// links.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Base URL that ids are appended to in the `Location` of created links, link prefix of
+    /// `[routes]` by default
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Overrides `creation_requires_auth` of `[token_config]`
+    #[serde(default)]
+    pub creation_requires_auth: Option<bool>,
+    #[serde(default = "default_id_length")]
+    pub id_length: u8,
+    /// Overrides `default_code` of `[redirects]`
+    #[serde(default)]
+    pub redirect_code: Option<RedirectCode>,
+}
+
+impl DomainConfig {
+    pub fn namespace(&self) -> &str {
+        self.namespace.as_deref().unwrap_or(&self.host)
+    }
+
+    /// Location of the link with this id on the domain
+    pub fn link_location(&self, routes: &RoutesConfig, id: &str) -> String {
+        let base_url = self.base_url.as_deref().unwrap_or(&routes.link_prefix);
+        format!("{}/{}", base_url.trim_end_matches('/'), id)
+    }
+}
+
+impl Default for DomainConfig {
+    /// Domain used for all requests if no domains are configured
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            namespace: None,
+            base_url: None,
+            creation_requires_auth: None,
+            id_length: default_id_length(),
+            redirect_code: None,
+        }
+    }
+}
+
+/// Shorter ids run out too quickly for random ids to find a free one
+pub const MIN_ID_LENGTH: u8 = 4;
+
+const fn default_id_length() -> u8 {
+    7
+}
@@ -0,0 +1,471 @@
+use std::fmt;
+
+use chrono::Duration;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Seconds of the units of both formats. Years and months have a fixed length of 365 and 30 days.
+const YEAR: i64 = 365 * DAY;
+const MONTH: i64 = 30 * DAY;
+const WEEK: i64 = 7 * DAY;
+const DAY: i64 = 24 * HOUR;
+const HOUR: i64 = 60 * MINUTE;
+const MINUTE: i64 = 60;
+
+/// Invalid duration, with the input it was parsed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DurationError {
+    input: String,
+    kind: DurationErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DurationErrorKind {
+    Empty,
+    ExpectedNumber { position: usize },
+    MissingUnit { number: String },
+    UnknownUnit { unit: char, position: usize },
+    RepeatedUnit { unit: char },
+    IsoUnknownUnit { unit: char, position: usize },
+    IsoOrder { unit: char },
+    IsoMissingTime,
+    IsoNoComponents,
+    IsoFraction { unit: char },
+    OutOfRange,
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let input = &self.input;
+        match &self.kind {
+            DurationErrorKind::Empty => write!(f, "Duration is empty"),
+            DurationErrorKind::ExpectedNumber { position } => write!(
+                f,
+                "Expected a number at position {position} of duration `{input}`"
+            ),
+            DurationErrorKind::MissingUnit { number } => write!(
+                f,
+                "Missing unit after `{number}` in duration `{input}`, expected one of Y, M, w, d, h, m, s"
+            ),
+            DurationErrorKind::UnknownUnit { unit, position } => write!(
+                f,
+                "Unknown unit `{unit}` at position {position} of duration `{input}`, expected one of Y, M, w, d, h, m, s"
+            ),
+            DurationErrorKind::RepeatedUnit { unit } => {
+                write!(f, "Unit `{unit}` is used more than once in duration `{input}`")
+            }
+            DurationErrorKind::IsoUnknownUnit { unit, position } => write!(
+                f,
+                "Unknown unit `{unit}` at position {position} of ISO 8601 duration `{input}`, expected Y, M, W, D before `T` or H, M, S after it"
+            ),
+            DurationErrorKind::IsoOrder { unit } => write!(
+                f,
+                "Unit `{unit}` is out of order in ISO 8601 duration `{input}`, expected PnYnMnWnDTnHnMnS"
+            ),
+            DurationErrorKind::IsoMissingTime => write!(
+                f,
+                "`T` must be followed by hours, minutes or seconds in ISO 8601 duration `{input}`"
+            ),
+            DurationErrorKind::IsoNoComponents => {
+                write!(f, "ISO 8601 duration `{input}` has no components")
+            }
+            DurationErrorKind::IsoFraction { unit } => write!(
+                f,
+                "Only seconds can have a fraction, not `{unit}`, in ISO 8601 duration `{input}`"
+            ),
+            DurationErrorKind::OutOfRange => write!(f, "Duration `{input}` is too long"),
+        }
+    }
+}
+
+impl std::error::Error for DurationError {}
+
+/// Parses a compound duration like `1w3d` or `2h 30m`, or an ISO 8601 duration like `P14D` or
+/// `PT1H30M`
+pub fn parse_duration(input: &str) -> Result<Duration, DurationError> {
+    let value = input.trim();
+    let error = |kind| DurationError {
+        input: value.to_owned(),
+        kind,
+    };
+    if value.is_empty() {
+        return Err(error(DurationErrorKind::Empty));
+    }
+    // ISO 8601-2 allows negative durations, which `format_duration` writes as well
+    let (negative, components) = if let Some(iso) = value.strip_prefix("-P") {
+        (true, parse_iso(iso, 2).map_err(error)?)
+    } else if let Some(iso) = value.strip_prefix('P') {
+        (false, parse_iso(iso, 1).map_err(error)?)
+    } else {
+        (false, parse_compound(value).map_err(error)?)
+    };
+
+    let mut seconds = 0i64;
+    let mut nanos = 0i64;
+    for Component {
+        amount,
+        fraction,
+        unit_seconds,
+    } in components
+    {
+        seconds = amount
+            .checked_mul(unit_seconds)
+            .and_then(|component| seconds.checked_add(component))
+            .ok_or_else(|| error(DurationErrorKind::OutOfRange))?;
+        nanos += fraction;
+    }
+    let duration = Duration::try_seconds(seconds)
+        .and_then(|duration| duration.checked_add(&Duration::nanoseconds(nanos)))
+        .ok_or_else(|| error(DurationErrorKind::OutOfRange))?;
+    Ok(if negative { -duration } else { duration })
+}
+
+/// Amount of a unit, `fraction` is in nanoseconds and only set for ISO 8601 seconds
+struct Component {
+    amount: i64,
+    fraction: i64,
+    unit_seconds: i64,
+}
+
+/// Splits the leading digits off the input
+fn take_number(input: &str) -> (&str, &str) {
+    let end = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    input.split_at(end)
+}
+
+fn parse_amount(number: &str) -> Result<i64, DurationErrorKind> {
+    number.parse().map_err(|_| DurationErrorKind::OutOfRange)
+}
+
+fn parse_compound(value: &str) -> Result<Vec<Component>, DurationErrorKind> {
+    let mut components = Vec::new();
+    let mut seen = Vec::new();
+    let mut rest = value;
+    while !rest.is_empty() {
+        let position = value.len() - rest.len();
+        let (number, after) = take_number(rest);
+        if number.is_empty() {
+            return Err(DurationErrorKind::ExpectedNumber { position });
+        }
+        let unit_position = value.len() - after.len();
+        let Some(unit) = after.chars().next() else {
+            return Err(DurationErrorKind::MissingUnit {
+                number: number.to_owned(),
+            });
+        };
+        let unit_seconds = match unit {
+            'Y' => YEAR,
+            'M' => MONTH,
+            'w' => WEEK,
+            'd' => DAY,
+            'h' | 'H' => HOUR,
+            'm' => MINUTE,
+            's' => 1,
+            _ if unit.is_whitespace() => {
+                return Err(DurationErrorKind::MissingUnit {
+                    number: number.to_owned(),
+                })
+            }
+            _ => {
+                return Err(DurationErrorKind::UnknownUnit {
+                    unit,
+                    position: unit_position,
+                })
+            }
+        };
+        if seen.contains(&unit_seconds) {
+            return Err(DurationErrorKind::RepeatedUnit { unit });
+        }
+        seen.push(unit_seconds);
+
+        components.push(Component {
+            amount: parse_amount(number)?,
+            fraction: 0,
+            unit_seconds,
+        });
+        rest = after[unit.len_utf8()..].trim_start();
+    }
+    Ok(components)
+}
+
+/// Parses what follows the `P` of an ISO 8601 duration, which starts at the offset of the input
+fn parse_iso(value: &str, offset: usize) -> Result<Vec<Component>, DurationErrorKind> {
+    const DATE_UNITS: [(char, i64); 4] = [('Y', YEAR), ('M', MONTH), ('W', WEEK), ('D', DAY)];
+    const TIME_UNITS: [(char, i64); 3] = [('H', HOUR), ('M', MINUTE), ('S', 1)];
+
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => {
+            if time.is_empty() {
+                return Err(DurationErrorKind::IsoMissingTime);
+            }
+            (date, Some(time))
+        }
+        None => (value, None),
+    };
+
+    let mut components = parse_iso_part(date, offset, &DATE_UNITS)?;
+    if let Some(time) = time {
+        components.extend(parse_iso_part(time, offset + date.len() + 1, &TIME_UNITS)?);
+    }
+    if components.is_empty() {
+        return Err(DurationErrorKind::IsoNoComponents);
+    }
+    Ok(components)
+}
+
+/// Parses the date or time part of an ISO 8601 duration. Units must appear in the given order.
+fn parse_iso_part(
+    part: &str,
+    offset: usize,
+    units: &[(char, i64)],
+) -> Result<Vec<Component>, DurationErrorKind> {
+    let mut components = Vec::new();
+    let mut next_unit = 0;
+    let mut rest = part;
+    while !rest.is_empty() {
+        let position = offset + part.len() - rest.len();
+        let (number, after) = take_number(rest);
+        if number.is_empty() {
+            return Err(DurationErrorKind::ExpectedNumber { position });
+        }
+        let (fraction, after) = match after.strip_prefix(['.', ',']) {
+            Some(after) => take_number(after),
+            None => ("", after),
+        };
+        let Some(unit) = after.chars().next() else {
+            return Err(DurationErrorKind::MissingUnit {
+                number: number.to_owned(),
+            });
+        };
+        let Some(index) = units.iter().position(|(name, _)| *name == unit) else {
+            return Err(DurationErrorKind::IsoUnknownUnit {
+                unit,
+                position: offset + part.len() - after.len(),
+            });
+        };
+        if index < next_unit {
+            return Err(DurationErrorKind::IsoOrder { unit });
+        }
+        next_unit = index + 1;
+
+        let (_, unit_seconds) = units[index];
+        if !fraction.is_empty() && unit_seconds != 1 {
+            return Err(DurationErrorKind::IsoFraction { unit });
+        }
+        components.push(Component {
+            amount: parse_amount(number)?,
+            fraction: format!("{fraction:0<9}")[..9].parse().unwrap_or(0),
+            unit_seconds,
+        });
+        rest = &after[unit.len_utf8()..];
+    }
+    Ok(components)
+}
+
+/// Formats the duration as ISO 8601 in days, hours, minutes and seconds, e.g. `P14D` or
+/// `PT1H30M`. Parsing the result gives the same duration.
+pub fn format_duration(duration: Duration) -> String {
+    let sign = if duration < Duration::zero() { "-" } else { "" };
+    let duration = duration.abs();
+    let seconds = duration.num_seconds();
+    let nanos = duration.subsec_nanos();
+
+    let mut formatted = format!("{sign}P");
+    let days = seconds / DAY;
+    if days > 0 {
+        formatted.push_str(&format!("{days}D"));
+    }
+    let (hours, minutes, seconds) = (
+        seconds % DAY / HOUR,
+        seconds % HOUR / MINUTE,
+        seconds % MINUTE,
+    );
+    if hours > 0 || minutes > 0 || seconds > 0 || nanos > 0 || days == 0 {
+        formatted.push('T');
+        if hours > 0 {
+            formatted.push_str(&format!("{hours}H"));
+        }
+        if minutes > 0 {
+            formatted.push_str(&format!("{minutes}M"));
+        }
+        if nanos > 0 {
+            let fraction = format!("{nanos:09}");
+            formatted.push_str(&format!("{seconds}.{}S", fraction.trim_end_matches('0')));
+        } else if seconds > 0 || (hours == 0 && minutes == 0) {
+            formatted.push_str(&format!("{seconds}S"));
+        }
+    }
+    formatted
+}
+
+/// Deserializes a duration in either format of [`parse_duration`]
+pub fn deserialize_duration<'de, D: Deserializer<'de>>(des: D) -> Result<Duration, D::Error> {
+    let value = String::deserialize(des)?;
+    parse_duration(&value).map_err(serde::de::Error::custom)
+}
+
+/// Deserializes a duration like [`deserialize_duration`], rejecting negative ones
+pub fn deserialize_period<'de, D: Deserializer<'de>>(des: D) -> Result<Duration, D::Error> {
+    let value = String::deserialize(des)?;
+    let duration = parse_duration(&value).map_err(serde::de::Error::custom)?;
+    if duration < Duration::zero() {
+        return Err(serde::de::Error::custom(format!(
+            "Duration `{value}` must not be negative"
+        )));
+    }
+    Ok(duration)
+}
+
+/// Serializes a duration as formatted by [`format_duration`]
+pub fn serialize_duration<S: Serializer>(duration: &Duration, ser: S) -> Result<S::Ok, S::Error> {
+    format_duration(*duration).serialize(ser)
+}
+
+/// Deserializes an optional duration like [`deserialize_duration`]
+pub fn deserialize_optional_duration<'de, D: Deserializer<'de>>(
+    des: D,
+) -> Result<Option<Duration>, D::Error> {
+    Option::<String>::deserialize(des)?
+        .map(|value| parse_duration(&value).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Serializes an optional duration like [`serialize_duration`]
+pub fn serialize_optional_duration<S: Serializer>(
+    duration: &Option<Duration>,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
+    duration.map(format_duration).serialize(ser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(input: &str) -> DurationErrorKind {
+        parse_duration(input).unwrap_err().kind
+    }
+
+    #[test]
+    fn parses_compound_durations() {
+        assert_eq!(parse_duration("1w3d"), Ok(Duration::days(10)));
+        assert_eq!(
+            parse_duration("2h 30m"),
+            Ok(Duration::hours(2) + Duration::minutes(30))
+        );
+        assert_eq!(parse_duration(" 90s "), Ok(Duration::seconds(90)));
+        assert_eq!(
+            parse_duration("1Y1M"),
+            Ok(Duration::days(365) + Duration::days(30))
+        );
+    }
+
+    #[test]
+    fn parses_iso_durations() {
+        assert_eq!(parse_duration("P14D"), Ok(Duration::days(14)));
+        assert_eq!(
+            parse_duration("PT1H30M"),
+            Ok(Duration::hours(1) + Duration::minutes(30))
+        );
+        assert_eq!(
+            parse_duration("P1DT0.5S"),
+            Ok(Duration::days(1) + Duration::milliseconds(500))
+        );
+        assert_eq!(parse_duration("PT1,25S"), Ok(Duration::milliseconds(1250)));
+    }
+
+    #[test]
+    fn parses_negative_iso_durations() {
+        assert_eq!(parse_duration("-P1D"), Ok(-Duration::days(1)));
+        assert_eq!(parse_duration("-PT1M"), Ok(-Duration::minutes(1)));
+        // Compound durations don't have a sign
+        assert_eq!(
+            kind("-1d"),
+            DurationErrorKind::ExpectedNumber { position: 0 }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        assert_eq!(kind(""), DurationErrorKind::Empty);
+        assert_eq!(kind("  "), DurationErrorKind::Empty);
+        assert_eq!(kind("d"), DurationErrorKind::ExpectedNumber { position: 0 });
+        assert_eq!(
+            kind("1d x"),
+            DurationErrorKind::ExpectedNumber { position: 3 }
+        );
+        assert_eq!(
+            kind("15"),
+            DurationErrorKind::MissingUnit {
+                number: "15".to_owned()
+            }
+        );
+        assert_eq!(
+            kind("1 d"),
+            DurationErrorKind::MissingUnit {
+                number: "1".to_owned()
+            }
+        );
+        assert_eq!(
+            kind("3x"),
+            DurationErrorKind::UnknownUnit {
+                unit: 'x',
+                position: 1
+            }
+        );
+        assert_eq!(kind("1d2d"), DurationErrorKind::RepeatedUnit { unit: 'd' });
+        assert_eq!(
+            kind("P1X"),
+            DurationErrorKind::IsoUnknownUnit {
+                unit: 'X',
+                position: 2
+            }
+        );
+        assert_eq!(kind("P1D1Y"), DurationErrorKind::IsoOrder { unit: 'Y' });
+        assert_eq!(kind("P1DT"), DurationErrorKind::IsoMissingTime);
+        assert_eq!(kind("P"), DurationErrorKind::IsoNoComponents);
+        assert_eq!(kind("P1.5D"), DurationErrorKind::IsoFraction { unit: 'D' });
+    }
+
+    #[test]
+    fn rejects_durations_out_of_range() {
+        assert_eq!(kind("99999999999999999999s"), DurationErrorKind::OutOfRange);
+        assert_eq!(kind("9223372036854775807w"), DurationErrorKind::OutOfRange);
+        assert_eq!(
+            kind("PT9223372036854775807S"),
+            DurationErrorKind::OutOfRange
+        );
+        // The seconds fit, adding the fraction to them doesn't
+        assert_eq!(kind("PT9223372036854775.9S"), DurationErrorKind::OutOfRange);
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::zero()), "PT0S");
+        assert_eq!(format_duration(Duration::days(14)), "P14D");
+        assert_eq!(
+            format_duration(Duration::hours(1) + Duration::minutes(30)),
+            "PT1H30M"
+        );
+        assert_eq!(format_duration(-Duration::days(1)), "-P1D");
+        assert_eq!(format_duration(Duration::milliseconds(1500)), "PT1.5S");
+    }
+
+    #[test]
+    fn formatted_durations_parse_to_the_same_duration() {
+        for duration in [
+            Duration::zero(),
+            Duration::seconds(1),
+            Duration::minutes(90),
+            Duration::days(14),
+            Duration::days(400) + Duration::seconds(1),
+            Duration::hours(25) + Duration::milliseconds(500),
+            Duration::nanoseconds(1_000_000_001),
+            -Duration::hours(1),
+            -(Duration::days(2) + Duration::nanoseconds(1)),
+        ] {
+            assert_eq!(parse_duration(&format_duration(duration)), Ok(duration));
+        }
+    }
+}
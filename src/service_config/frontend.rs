@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct FrontendConfig {
+    /// Directory with the built web frontend, containing `index.html`
+    pub assets_dir: PathBuf,
+    /// Path prefix to serve the frontend under, empty for the root of the domain
+    #[serde(default)]
+    pub path: String,
+}
@@ -1,68 +1,41 @@
 use std::sync::Arc;
 
 use chrono::Duration;
-use serde::{de::Visitor, Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer};
+
+use super::duration::deserialize_period;
 
 #[derive(Debug, Deserialize, Clone)]
-#[serde(rename = "snake_case")]
+#[serde(rename = "snake_case", deny_unknown_fields)]
 pub struct IpRecordingConfig {
     #[serde(
         default = "default_retention_period",
-        deserialize_with = "deserialize_retention_period"
+        deserialize_with = "deserialize_period"
     )]
     pub retention_period: Duration,
-    #[serde(default = "default_check_period")]
+    /// Cron expression with a seconds field, which is added to expressions without one
+    #[serde(
+        default = "default_check_period",
+        deserialize_with = "deserialize_cron"
+    )]
     pub retention_check_period: Arc<str>,
 }
 
 fn default_check_period() -> Arc<str> {
-    "0 0 * * *".into()
+    "0 0 0 * * *".into()
 }
 
-fn default_retention_period() -> Duration {
-    Duration::weeks(2)
+/// The scheduler requires seconds, common five field expressions run at second 0
+pub fn deserialize_cron<'de, D: Deserializer<'de>>(des: D) -> Result<Arc<str>, D::Error> {
+    let value = String::deserialize(des)?;
+    let value = value.trim();
+    Ok(if value.split_whitespace().count() == 5 {
+        format!("0 {value}").into()
+    } else {
+        value.into()
+    })
 }
 
-fn deserialize_retention_period<'de, D: Deserializer<'de>>(des: D) -> Result<Duration, D::Error> {
-    struct PeriodVisitor;
-
-    impl Visitor<'_> for PeriodVisitor {
-        type Value = Duration;
-
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            formatter.write_str("A string signifying a period")
-        }
-
-        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-        where
-            E: serde::de::Error,
-        {
-            let amount: i64 = v[..(v.len() - 1)]
-                .parse()
-                .map_err(|e| serde::de::Error::custom(e))?;
-
-            if v.ends_with('Y') {
-                Ok(Duration::days(amount * 365))
-            } else if v.ends_with('M') {
-                Ok(Duration::days(amount * 30))
-            } else if v.ends_with('w') {
-                Ok(Duration::weeks(amount))
-            } else if v.ends_with('d') {
-                Ok(Duration::days(amount))
-            } else if v.ends_with('h') || v.ends_with('H') {
-                Ok(Duration::hours(amount))
-            } else if v.ends_with('m') {
-                Ok(Duration::minutes(amount))
-            } else if v.ends_with('s') {
-                Ok(Duration::seconds(amount))
-            } else {
-                Err(serde::de::Error::custom(format!(
-                    "Invalid period suffix: {}",
-                    &v[v.len()..]
-                )))
-            }
-        }
-    }
-
-    des.deserialize_str(PeriodVisitor)
+fn default_retention_period() -> Duration {
+    Duration::weeks(2)
 }
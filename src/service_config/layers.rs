@@ -0,0 +1,179 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+use toml::{Table, Value};
+
+/// Prefix of environment variables overriding config options. Nested keys are separated with
+/// `__`, e.g. `SAYLESS_CONFIG__IP_RECORDING__RETENTION_PERIOD`. Other `SAYLESS_*` variables belong
+/// to other tools and aren't options.
+pub const ENV_PREFIX: &str = "SAYLESS_CONFIG__";
+
+/// Environment variable setting the option at the dotted key
+#[derive(Debug, Clone)]
+pub struct EnvOverride {
+    pub key: String,
+    pub var: String,
+}
+
+impl EnvOverride {
+    /// Whether the override sets the key or a table containing it
+    pub fn covers(&self, key: &str) -> bool {
+        key == self.key
+            || key
+                .strip_prefix(&self.key)
+                .is_some_and(|rest| rest.starts_with('.'))
+    }
+}
+
+/// Applies `SAYLESS_CONFIG__*` variables on top of the options of the file. Values are parsed as TOML,
+/// so `5`, `true` and `{}` are a number, a boolean and an empty table, and values that aren't
+/// valid TOML are taken as strings. Returns the applied overrides, or the variables that can't
+/// be applied.
+pub fn apply_env_overrides(
+    table: &mut Table,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<EnvOverride>, Vec<(String, String)>> {
+    // Sorted, so that a table is set before the keys inside it
+    let mut vars = vars
+        .into_iter()
+        .filter(|(var, _)| var.starts_with(ENV_PREFIX))
+        .collect::<Vec<_>>();
+    vars.sort();
+
+    let mut overrides = Vec::new();
+    let mut errors = Vec::new();
+    for (var, raw) in vars {
+        let path = var[ENV_PREFIX.len()..]
+            .split("__")
+            .map(str::to_ascii_lowercase)
+            .collect::<Vec<_>>();
+        match set_path(table, &path, parse_env_value(&raw)) {
+            Ok(()) => overrides.push(EnvOverride {
+                key: path.join("."),
+                var,
+            }),
+            Err(message) => errors.push((var, message)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(overrides)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_env_value(raw: &str) -> Value {
+    format!("value = {raw}")
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_owned()))
+}
+
+/// Sets the value at the path, creating tables on the way. Numeric parts index arrays of tables.
+fn set_path(table: &mut Table, path: &[String], value: Value) -> Result<(), String> {
+    let (last, parents) = path.split_last().expect("Split always returns a part");
+    if path.iter().any(String::is_empty) {
+        return Err("Key parts must not be empty".to_owned());
+    }
+
+    let mut current = table;
+    let mut parts = parents.iter();
+    while let Some(part) = parts.next() {
+        let entry = current
+            .entry(part.as_str())
+            .or_insert_with(|| Value::Table(Table::new()));
+        current = match entry {
+            Value::Table(nested) => nested,
+            Value::Array(array) => {
+                let index = parts
+                    .next()
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .ok_or_else(|| format!("`{part}` is an array, expected an index after it"))?;
+                let len = array.len();
+                match array.get_mut(index) {
+                    Some(Value::Table(nested)) => nested,
+                    Some(_) => return Err(format!("Element {index} of `{part}` isn't a table")),
+                    None => {
+                        return Err(format!(
+                            "`{part}` has {len} element(s), index {index} is out of range"
+                        ))
+                    }
+                }
+            }
+            _ => return Err(format!("`{part}` isn't a table")),
+        };
+    }
+    current.insert(last.clone(), value);
+    Ok(())
+}
+
+/// Where an option gets its value from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueSource {
+    File { line: Option<usize> },
+    Env { var: String },
+}
+
+/// Options set by the config file and the environment with their values and sources. All other
+/// options have their default values.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    pub file: Option<PathBuf>,
+    pub values: BTreeMap<String, (String, ValueSource)>,
+}
+
+impl ConfigSources {
+    /// Records every value of the merged options, looking up where it comes from
+    pub fn collect(
+        file: Option<PathBuf>,
+        table: &Table,
+        mut source_of: impl FnMut(&str) -> ValueSource,
+    ) -> Self {
+        let mut values = BTreeMap::new();
+        let mut pending = table
+            .iter()
+            .map(|(key, value)| (key.clone(), value))
+            .collect::<Vec<_>>();
+        while let Some((key, value)) = pending.pop() {
+            match value {
+                Value::Table(nested) if !nested.is_empty() => pending.extend(
+                    nested
+                        .iter()
+                        .map(|(nested_key, value)| (format!("{key}.{nested_key}"), value)),
+                ),
+                Value::Array(array) if !array.is_empty() && array.iter().all(Value::is_table) => {
+                    pending.extend(
+                        array
+                            .iter()
+                            .enumerate()
+                            .map(|(index, value)| (format!("{key}.{index}"), value)),
+                    )
+                }
+                _ => {
+                    let source = source_of(&key);
+                    values.insert(key, (value.to_string(), source));
+                }
+            }
+        }
+        Self { file, values }
+    }
+}
+
+impl fmt::Display for ConfigSources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = self.file.as_ref().map_or_else(
+            || "config file".to_owned(),
+            |file| file.display().to_string(),
+        );
+        for (key, (value, source)) in &self.values {
+            match source {
+                ValueSource::File { line: Some(line) } => {
+                    writeln!(f, "{key} = {value} ({file}:{line})")?
+                }
+                ValueSource::File { line: None } => writeln!(f, "{key} = {value} ({file})")?,
+                ValueSource::Env { var } => writeln!(f, "{key} = {value} (env {var})")?,
+            }
+        }
+        f.write_str("Other options have their default values")
+    }
+}
@@ -0,0 +1,33 @@
+use chrono::Duration;
+use serde::Deserialize;
+
+use super::duration::deserialize_period;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct LinkPasswordsConfig {
+    #[serde(default = "default_max_failed_attempts")]
+    pub max_failed_attempts: u32,
+    #[serde(
+        default = "default_lockout_period",
+        deserialize_with = "deserialize_period"
+    )]
+    pub lockout_period: Duration,
+}
+
+impl Default for LinkPasswordsConfig {
+    fn default() -> Self {
+        Self {
+            max_failed_attempts: default_max_failed_attempts(),
+            lockout_period: default_lockout_period(),
+        }
+    }
+}
+
+const fn default_max_failed_attempts() -> u32 {
+    5
+}
+
+fn default_lockout_period() -> Duration {
+    Duration::minutes(15)
+}
@@ -0,0 +1,64 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct LoggingConfig {
+    #[serde(default)]
+    pub format: LogFormat,
+    /// Logs a line for every request if set
+    #[serde(default)]
+    pub access_log: Option<AccessLogConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines, with the spans of the line as a prefix
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of the current span and the span list
+    Json,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct AccessLogConfig {
+    /// Fields of each line, besides the request id that every line has
+    #[serde(default = "default_fields")]
+    pub fields: Vec<AccessLogField>,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            fields: default_fields(),
+        }
+    }
+}
+
+fn default_fields() -> Vec<AccessLogField> {
+    vec![
+        AccessLogField::ClientIp,
+        AccessLogField::Method,
+        AccessLogField::Path,
+        AccessLogField::Status,
+        AccessLogField::LatencyMs,
+    ]
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLogField {
+    ClientIp,
+    Method,
+    /// Path of the request URI, without the query
+    Path,
+    Query,
+    /// Route the request matched, e.g. `/l/{id}`
+    Route,
+    Host,
+    UserAgent,
+    Referer,
+    Status,
+    LatencyMs,
+}
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer};
+
+use super::ip_recording::deserialize_cron;
+
+/// Cron schedules of the maintenance jobs that aren't tied to another table. Jobs without a
+/// schedule only run when triggered.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct MaintenanceConfig {
+    #[serde(default, deserialize_with = "deserialize_schedule")]
+    pub expired_tokens: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "deserialize_schedule")]
+    pub expired_links: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "deserialize_schedule")]
+    pub strike_decay: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "deserialize_schedule")]
+    pub orphaned_origins: Option<Arc<str>>,
+}
+
+fn deserialize_schedule<'de, D: Deserializer<'de>>(des: D) -> Result<Option<Arc<str>>, D::Error> {
+    deserialize_cron(des).map(Some)
+}
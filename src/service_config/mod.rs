@@ -1,42 +1,155 @@
-use std::{error::Error, path::PathBuf, sync::Arc};
+use std::{error::Error, path::PathBuf};
 
 use serde::Deserialize;
 
-use self::{ip_recording::IpRecordingConfig, token::TokenConfig};
+use self::{
+    audit_log::AuditLogConfig,
+    domains::DomainConfig,
+    frontend::FrontendConfig,
+    ip_recording::IpRecordingConfig,
+    layers::ConfigSources,
+    link_passwords::LinkPasswordsConfig,
+    logging::LoggingConfig,
+    maintenance::MaintenanceConfig,
+    redirect_cache::RedirectCacheConfig,
+    redirects::RedirectsConfig,
+    routes::RoutesConfig,
+    server::ServerConfig,
+    strikes::StrikesConfig,
+    token::{MasterTokens, TokenConfig},
+};
+use crate::secrets::read_secret;
 
+pub mod audit_log;
+pub mod domains;
+pub mod duration;
+pub mod frontend;
 pub mod ip_recording;
+pub mod layers;
+pub mod link_passwords;
+pub mod logging;
+pub mod maintenance;
+pub mod redirect_cache;
+pub mod redirects;
+pub mod reload;
+pub mod routes;
+pub mod server;
+pub mod strikes;
 pub mod token;
+pub mod validation;
 
 #[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct ServiceConfig {
     #[serde(default = "default_max_strikes")]
     pub max_strikes: u16,
     #[serde(default)]
+    pub strikes: StrikesConfig,
+    #[serde(default = "default_max_bulk_links")]
+    pub max_bulk_links: u16,
+    #[serde(default)]
     pub ip_recording: Option<IpRecordingConfig>,
     #[serde(default)]
     pub token_config: Option<TokenConfig>,
     #[serde(default)]
     pub log_level: Option<log::Level>,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub audit_log: AuditLogConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub link_passwords: LinkPasswordsConfig,
+    #[serde(default)]
+    pub redirects: RedirectsConfig,
+    #[serde(default)]
+    pub redirect_cache: RedirectCacheConfig,
+    #[serde(default)]
+    pub domains: Vec<DomainConfig>,
+    #[serde(default)]
+    pub routes: RoutesConfig,
+    #[serde(default)]
+    pub frontend: Option<FrontendConfig>,
+    #[serde(default)]
+    pub server: ServerConfig,
+    /// Where the options that aren't defaults are set
+    #[serde(skip)]
+    pub sources: ConfigSources,
+}
+
+impl ServiceConfig {
+    /// Configured domain with this host name. If no domains are configured, all requests use
+    /// the default domain.
+    pub fn domain(&self, host: Option<&str>) -> Option<DomainConfig> {
+        if self.domains.is_empty() {
+            return Some(DomainConfig::default());
+        }
+        let host = host?;
+        self.domains
+            .iter()
+            .find(|domain| domain.host.eq_ignore_ascii_case(host))
+            .cloned()
+    }
+
+    /// Log level of `log_level`, or of the `RUST_LOG` environment variable if it's not set
+    pub fn log_level_filter(&self) -> log::LevelFilter {
+        self.log_level
+            .map(|level| level.to_level_filter())
+            .or_else(|| {
+                dotenvy::var("RUST_LOG")
+                    .ok()
+                    .and_then(|level| level.parse().ok())
+            })
+            .unwrap_or(log::LevelFilter::Info)
+    }
 }
 
 const fn default_max_strikes() -> u16 {
     30
 }
 
+const fn default_max_bulk_links() -> u16 {
+    100
+}
+
+/// Path of the config file, `CONFIG_FILE` or `config.toml` by default. `None` if `CONFIG_FILE`
+/// isn't set and there's no `config.toml`, then options only come from the environment.
+pub fn config_path() -> Option<PathBuf> {
+    match dotenvy::var("CONFIG_FILE") {
+        Ok(path) => Some(path.into()),
+        Err(_) => {
+            let path = PathBuf::from("config.toml");
+            path.exists().then_some(path)
+        }
+    }
+}
+
+/// Reads and validates the config file with the `SAYLESS_CONFIG__*` environment variables
+/// applied, without looking up the master token
+pub async fn read_config() -> Result<ServiceConfig, Box<dyn Error + Send + Sync>> {
+    let config_path = config_path();
+    let config_str = match &config_path {
+        Some(path) => tokio::fs::read_to_string(path).await?,
+        None => String::new(),
+    };
+    Ok(validation::parse_config(
+        config_path,
+        &config_str,
+        dotenvy::vars(),
+    )?)
+}
+
+/// Reads the config like [`read_config`], with the master tokens from `MASTER_TOKEN` or the file
+/// at `MASTER_TOKEN_FILE` if the token system is enabled
 pub async fn get_config() -> Result<ServiceConfig, Box<dyn Error + Send + Sync>> {
-    let config_path: PathBuf = dotenvy::var("CONFIG_FILE")
-        .ok()
-        .unwrap_or_else(|| "config.toml".into())
-        .parse()?;
-    log::info!("Loading config from {}", config_path.to_str().unwrap());
-    let config_str = tokio::fs::read_to_string(config_path.as_path()).await?;
-    let mut config: ServiceConfig = toml::from_str(&config_str)?;
+    let mut config = read_config().await?;
     if let Some(tok_config) = &mut config.token_config {
-        tok_config.master_token = Arc::from(
-            dotenvy::var("MASTER_TOKEN")
-                .expect("Master token is required if token system is enabled")
-                .into_boxed_str(),
-        );
+        let secret = read_secret("MASTER_TOKEN")?.ok_or(
+            "MASTER_TOKEN or MASTER_TOKEN_FILE must be set if the token system is enabled",
+        )?;
+        tok_config.master_tokens =
+            MasterTokens::parse(&secret).ok_or("The master token must not be empty")?;
     }
     Ok(config)
 }
@@ -0,0 +1,31 @@
+use chrono::Duration;
+use serde::Deserialize;
+
+use super::duration::deserialize_period;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct RedirectCacheConfig {
+    /// Maximum amount of cached links, 0 disables the cache
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+    #[serde(default = "default_ttl", deserialize_with = "deserialize_period")]
+    pub ttl: Duration,
+}
+
+impl Default for RedirectCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_capacity(),
+            ttl: default_ttl(),
+        }
+    }
+}
+
+const fn default_capacity() -> usize {
+    10_000
+}
+
+fn default_ttl() -> Duration {
+    Duration::minutes(1)
+}
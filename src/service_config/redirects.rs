@@ -0,0 +1,31 @@
+use chrono::Duration;
+use serde::Deserialize;
+
+use super::duration::deserialize_period;
+use crate::json_schemas::redirect_code::RedirectCode;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct RedirectsConfig {
+    #[serde(default)]
+    pub default_code: RedirectCode,
+    /// How long clients may cache permanent redirects
+    #[serde(
+        default = "default_permanent_cache_max_age",
+        deserialize_with = "deserialize_period"
+    )]
+    pub permanent_cache_max_age: Duration,
+}
+
+impl Default for RedirectsConfig {
+    fn default() -> Self {
+        Self {
+            default_code: RedirectCode::default(),
+            permanent_cache_max_age: default_permanent_cache_max_age(),
+        }
+    }
+}
+
+fn default_permanent_cache_max_age() -> Duration {
+    Duration::days(1)
+}
@@ -0,0 +1,185 @@
+use std::{
+    error::Error,
+    path::Path,
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    time::MissedTickBehavior,
+};
+
+use super::{config_path, get_config, layers::ConfigSources, ServiceConfig};
+
+/// How often the config file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Options that are only read at startup, by the logger, the listeners, the router, the redirect
+/// cache and the scheduler
+const RESTART_ONLY_OPTIONS: [&str; 8] = [
+    "server",
+    "logging.format",
+    "routes",
+    "frontend",
+    "redirect_cache",
+    "ip_recording.retention_check_period",
+    "audit_log.retention_check_period",
+    "maintenance",
+];
+
+/// Tables that can't be enabled or disabled without a restart, their options can be changed
+const RESTART_ONLY_TABLES: [&str; 2] = ["ip_recording", "token_config"];
+
+/// How often the config was reloaded since startup
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReloadStats {
+    pub count: u64,
+    pub last_reload: Option<DateTime<Utc>>,
+}
+
+struct Current {
+    config: Arc<ServiceConfig>,
+    stats: ReloadStats,
+}
+
+/// Config of the running service, replaced as a whole when it's reloaded. Every load sees a
+/// complete config, never a mix of old and new options.
+#[derive(Clone)]
+pub struct SharedConfig(Arc<RwLock<Current>>);
+
+impl SharedConfig {
+    pub fn new(config: ServiceConfig) -> Self {
+        Self(Arc::new(RwLock::new(Current {
+            config: Arc::new(config),
+            stats: ReloadStats::default(),
+        })))
+    }
+
+    /// Current config
+    pub fn load(&self) -> Arc<ServiceConfig> {
+        Arc::clone(&self.0.read().unwrap_or_else(PoisonError::into_inner).config)
+    }
+
+    /// Current config with the reload stats of it
+    pub fn load_with_stats(&self) -> (Arc<ServiceConfig>, ReloadStats) {
+        let current = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        (Arc::clone(&current.config), current.stats)
+    }
+
+    /// Reads and validates the config and the master tokens again, and swaps them in. Invalid configs and changes of
+    /// options that need a restart are rejected, keeping the current config.
+    pub async fn reload(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let config = get_config().await?;
+        let current = self.load();
+
+        let restart_only = restart_only_changes(&current.sources, &config.sources);
+        if !restart_only.is_empty() {
+            return Err(format!(
+                "{} can only be changed by restarting the service",
+                restart_only
+                    .iter()
+                    .map(|key| format!("`{key}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .into());
+        }
+        if let Some(tok_config) = &config.token_config {
+            for weakness in tok_config.master_tokens.weaknesses() {
+                log::warn!("{weakness}");
+            }
+        }
+
+        crate::logging::set_level(config.log_level_filter());
+        log::debug!("Configuration sources:\n{}", config.sources);
+        let mut current = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        current.config = Arc::new(config);
+        current.stats = ReloadStats {
+            count: current.stats.count + 1,
+            last_reload: Some(Utc::now()),
+        };
+        Ok(())
+    }
+}
+
+/// Restart-only options and tables that differ between the configs
+fn restart_only_changes(current: &ConfigSources, new: &ConfigSources) -> Vec<&'static str> {
+    let values = |sources: &ConfigSources, key: &str| {
+        sources
+            .values
+            .iter()
+            .filter(|(option, _)| is_within(option, key))
+            .map(|(option, (value, _))| (option.clone(), value.clone()))
+            .collect::<Vec<_>>()
+    };
+    let is_set = |sources: &ConfigSources, key: &str| {
+        sources.values.keys().any(|option| is_within(option, key))
+    };
+
+    RESTART_ONLY_OPTIONS
+        .into_iter()
+        .filter(|key| values(current, key) != values(new, key))
+        .chain(
+            RESTART_ONLY_TABLES
+                .into_iter()
+                .filter(|key| is_set(current, key) != is_set(new, key)),
+        )
+        .collect()
+}
+
+/// Whether the option is the key or inside the table at it
+fn is_within(option: &str, key: &str) -> bool {
+    option == key
+        || option
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Reloads the config on SIGHUP and when the config file changes. Rejected reloads are logged
+/// and the current config is kept.
+pub async fn watch_config(config: SharedConfig) {
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => Some(hangup),
+        Err(why) => {
+            log::error!("Failed to listen for SIGHUP, the config is only reloaded when its file changes: {why}");
+            None
+        }
+    };
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut modified = file_modified_at();
+
+    loop {
+        let trigger = tokio::select! {
+            Some(()) = async {
+                match &mut hangup {
+                    Some(hangup) => hangup.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => "SIGHUP",
+            _ = interval.tick() => {
+                let file_modified = file_modified_at();
+                if file_modified == modified {
+                    continue;
+                }
+                modified = file_modified;
+                "config file change"
+            }
+        };
+
+        log::info!("Reloading config after {trigger}");
+        match config.reload().await {
+            Ok(()) => log::info!("Config reloaded"),
+            Err(why) => log::error!("Config reload rejected, keeping the current config: {why}"),
+        }
+    }
+}
+
+fn file_modified_at() -> Option<SystemTime> {
+    config_path()
+        .as_deref()
+        .and_then(|path: &Path| path.metadata().ok())
+        .and_then(|metadata| metadata.modified().ok())
+}
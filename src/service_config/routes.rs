@@ -0,0 +1,53 @@
+use serde::Deserialize;
+
+/// Prefix that routes were served under before it was configurable, always kept as an alias
+pub const LEGACY_PREFIX: &str = "/l";
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct RoutesConfig {
+    /// Prefix of short links, empty for links at the root of the domain
+    #[serde(default = "default_prefix")]
+    pub link_prefix: String,
+    /// Prefix of API endpoints
+    #[serde(default = "default_prefix")]
+    pub api_prefix: String,
+}
+
+impl RoutesConfig {
+    /// Prefixes to serve short links under, the configured one first
+    pub fn link_prefixes(&self) -> Vec<&str> {
+        with_legacy_prefix(&self.link_prefix)
+    }
+
+    /// Prefixes to serve API endpoints under, the configured one first
+    pub fn api_prefixes(&self) -> Vec<&str> {
+        with_legacy_prefix(&self.api_prefix)
+    }
+}
+
+fn with_legacy_prefix(prefix: &str) -> Vec<&str> {
+    if prefix == LEGACY_PREFIX {
+        vec![prefix]
+    } else {
+        vec![prefix, LEGACY_PREFIX]
+    }
+}
+
+impl Default for RoutesConfig {
+    fn default() -> Self {
+        Self {
+            link_prefix: default_prefix(),
+            api_prefix: default_prefix(),
+        }
+    }
+}
+
+fn default_prefix() -> String {
+    LEGACY_PREFIX.into()
+}
+
+/// Prefix must be empty or start with `/`, and must not end with `/`
+pub(super) fn is_valid_prefix(prefix: &str) -> bool {
+    prefix.is_empty() || (prefix.starts_with('/') && !prefix.ends_with('/'))
+}
@@ -0,0 +1,110 @@
+use std::{fmt, net::SocketAddr, path::PathBuf, str::FromStr};
+
+use serde::{Deserialize, Deserializer};
+
+/// Prefix of listen addresses of Unix domain sockets
+const UNIX_PREFIX: &str = "unix:";
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Addresses to listen on, all IPv4 interfaces on `PORT` if empty
+    #[serde(default)]
+    pub listen: Vec<ListenAddress>,
+    /// Permissions of Unix domain sockets, e.g. `0o660`
+    #[serde(default = "default_unix_socket_mode")]
+    pub unix_socket_mode: u32,
+    /// Serves TLS on the TCP addresses if set
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Only reachable through a reverse proxy, so its `X-Forwarded-Proto` can be trusted
+    #[serde(default)]
+    pub trusted_proxy: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen: Vec::new(),
+            unix_socket_mode: default_unix_socket_mode(),
+            tls: None,
+            trusted_proxy: false,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Configured addresses, or all IPv4 interfaces on the port of the `PORT` environment
+    /// variable if there are none
+    pub fn listen_addresses(&self) -> Result<Vec<ListenAddress>, String> {
+        if !self.listen.is_empty() {
+            return Ok(self.listen.clone());
+        }
+        let port = dotenvy::var("PORT").map_err(|_| {
+            "Set `listen` of `[server]` or the PORT environment variable".to_owned()
+        })?;
+        let port: u16 = port
+            .parse()
+            .map_err(|e| format!("Invalid PORT `{port}`: {e}"))?;
+        Ok(vec![ListenAddress::Tcp(SocketAddr::from((
+            [0, 0, 0, 0],
+            port,
+        )))])
+    }
+}
+
+const fn default_unix_socket_mode() -> u32 {
+    0o660
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain
+    pub cert: PathBuf,
+    /// PEM file with the private key
+    pub key: PathBuf,
+}
+
+/// TCP address like `0.0.0.0:8080` or `[::]:8080`, or Unix domain socket like
+/// `unix:/run/sayless.sock`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ListenAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddress {
+    type Err = String;
+
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = address.strip_prefix(UNIX_PREFIX) {
+            if path.is_empty() {
+                return Err(format!("Unix socket address `{address}` has no path"));
+            }
+            return Ok(Self::Unix(path.into()));
+        }
+        address.parse().map(Self::Tcp).map_err(|_| {
+            format!(
+                "Invalid listen address `{address}`, expected an IP and port like `0.0.0.0:8080` or `[::]:8080`, or `{UNIX_PREFIX}` followed by a socket path"
+            )
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ListenAddress {
+    fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+        String::deserialize(des)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for ListenAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(address) => address.fmt(f),
+            Self::Unix(path) => write!(f, "{UNIX_PREFIX}{}", path.display()),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Deserializer};
+
+use super::duration::deserialize_period;
+
+/// Expiry of strikes and duration of the bans they lead to
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename = "snake_case", deny_unknown_fields)]
+pub struct StrikesConfig {
+    /// One strike expires per period since the last strike of the IP, strikes never expire
+    /// without it
+    #[serde(default, deserialize_with = "deserialize_optional_period")]
+    pub decay_period: Option<Duration>,
+    /// Reaching `max_strikes` bans the IP for this long. Without it, the IP is banned for as long
+    /// as it has `max_strikes` strikes.
+    #[serde(default, deserialize_with = "deserialize_optional_period")]
+    pub ban_duration: Option<Duration>,
+    /// Each further ban of an IP lasts this many times longer than the previous one
+    #[serde(default = "default_ban_escalation")]
+    pub ban_escalation: f64,
+    #[serde(
+        default = "default_max_ban_duration",
+        deserialize_with = "deserialize_period"
+    )]
+    pub max_ban_duration: Duration,
+}
+
+impl Default for StrikesConfig {
+    fn default() -> Self {
+        Self {
+            decay_period: None,
+            ban_duration: None,
+            ban_escalation: default_ban_escalation(),
+            max_ban_duration: default_max_ban_duration(),
+        }
+    }
+}
+
+impl StrikesConfig {
+    /// Strikes left of `amount` recorded at `recorded_at`, and the time the remaining ones count
+    /// from, so partly elapsed periods aren't lost when they are written back
+    pub fn decay(
+        &self,
+        amount: u16,
+        recorded_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> (u16, DateTime<Utc>) {
+        let Some(period) = self
+            .decay_period
+            .filter(|period| *period > Duration::zero())
+        else {
+            return (amount, recorded_at);
+        };
+        let elapsed = (now - recorded_at).num_seconds().max(0);
+        let expired = (elapsed / period.num_seconds().max(1)).min(i64::from(amount));
+        // `expired` is at most `amount`, which fits both, and its periods end before `now`
+        let counts_from = period
+            .checked_mul(expired as i32)
+            .and_then(|expired| recorded_at.checked_add_signed(expired))
+            .unwrap_or(now);
+        (amount - expired as u16, counts_from)
+    }
+
+    /// Duration of the ban of an IP that was banned `previous_bans` times before, `None` if bans
+    /// only lift once strikes expire
+    pub fn ban_duration(&self, previous_bans: u16) -> Option<Duration> {
+        let base = self.ban_duration?;
+        let escalated = base.num_seconds() as f64 * self.ban_escalation.powi(previous_bans.into());
+        let max = self.max_ban_duration.num_seconds() as f64;
+        Some(Duration::try_seconds(escalated.min(max) as i64).unwrap_or(self.max_ban_duration))
+    }
+}
+
+fn deserialize_optional_period<'de, D: Deserializer<'de>>(
+    des: D,
+) -> Result<Option<Duration>, D::Error> {
+    deserialize_period(des).map(Some)
+}
+
+const fn default_ban_escalation() -> f64 {
+    1.0
+}
+
+fn default_max_ban_duration() -> Duration {
+    Duration::days(365)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::StrikesConfig;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    fn decaying(period: Duration) -> StrikesConfig {
+        StrikesConfig {
+            decay_period: Some(period),
+            ..StrikesConfig::default()
+        }
+    }
+
+    fn banning(duration: Duration, escalation: f64) -> StrikesConfig {
+        StrikesConfig {
+            ban_duration: Some(duration),
+            ban_escalation: escalation,
+            max_ban_duration: Duration::days(30),
+            ..StrikesConfig::default()
+        }
+    }
+
+    #[test]
+    fn strikes_never_expire_without_decay_period() {
+        let config = StrikesConfig::default();
+        assert_eq!(config.decay(5, at(0), at(1_000_000)), (5, at(0)));
+    }
+
+    #[test]
+    fn decay_keeps_partly_elapsed_period() {
+        let config = decaying(Duration::hours(1));
+        assert_eq!(
+            config.decay(5, at(0), at(2 * 3600 + 600)),
+            (3, at(2 * 3600))
+        );
+        assert_eq!(config.decay(5, at(0), at(3599)), (5, at(0)));
+    }
+
+    #[test]
+    fn decay_stops_at_zero() {
+        let config = decaying(Duration::hours(1));
+        assert_eq!(config.decay(2, at(0), at(10 * 3600)).0, 0);
+    }
+
+    #[test]
+    fn decay_ignores_time_going_back() {
+        let config = decaying(Duration::hours(1));
+        assert_eq!(config.decay(2, at(3600), at(0)), (2, at(3600)));
+    }
+
+    #[test]
+    fn decay_handles_long_periods() {
+        let config = decaying(Duration::days(100 * 365));
+        assert_eq!(config.decay(u16::MAX, at(0), at(1)), (u16::MAX, at(0)));
+    }
+
+    #[test]
+    fn bans_last_until_strikes_expire_without_ban_duration() {
+        assert_eq!(StrikesConfig::default().ban_duration(0), None);
+    }
+
+    #[test]
+    fn ban_duration_escalates() {
+        let config = banning(Duration::hours(1), 2.0);
+        assert_eq!(config.ban_duration(0), Some(Duration::hours(1)));
+        assert_eq!(config.ban_duration(1), Some(Duration::hours(2)));
+        assert_eq!(config.ban_duration(3), Some(Duration::hours(8)));
+    }
+
+    #[test]
+    fn ban_duration_without_escalation_stays_the_same() {
+        let config = banning(Duration::hours(1), 1.0);
+        assert_eq!(config.ban_duration(100), Some(Duration::hours(1)));
+    }
+
+    #[test]
+    fn ban_duration_is_capped() {
+        let config = banning(Duration::hours(1), 2.0);
+        assert_eq!(config.ban_duration(20), Some(Duration::days(30)));
+        assert_eq!(config.ban_duration(u16::MAX), Some(Duration::days(30)));
+    }
+}
@@ -1,15 +1,102 @@
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{collections::HashSet, fmt, sync::Arc};
+
+/// Master tokens shorter than this are reported as weak
+const MIN_MASTER_TOKEN_LENGTH: usize = 16;
+/// Master tokens that can't have this much entropy with their length and characters are
+/// reported as weak
+const MIN_MASTER_TOKEN_BITS: f64 = 64.0;
+/// Master tokens with fewer different characters than this are reported as weak
+const MIN_DISTINCT_CHARS: usize = 5;
 
 #[derive(Debug, Deserialize, Clone)]
-#[serde(rename = "snake_case")]
+#[serde(rename = "snake_case", deny_unknown_fields)]
 pub struct TokenConfig {
     #[serde(default)]
     pub creation_requires_auth: bool,
-    #[serde(skip_deserializing, default = "default_arc_str")]
-    pub master_token: Arc<str>,
+    #[serde(skip)]
+    pub master_tokens: MasterTokens,
+}
+
+/// Tokens with every permission, set by `MASTER_TOKEN` or `MASTER_TOKEN_FILE`. Several tokens
+/// can be set, one per line, so that they can be rotated without downtime.
+#[derive(Clone, Default)]
+pub struct MasterTokens(Arc<[String]>);
+
+impl MasterTokens {
+    /// Tokens on the lines of the secret, ignoring surrounding whitespace and empty lines.
+    /// `None` if there are none.
+    pub fn parse(secret: &str) -> Option<Self> {
+        let tokens = secret
+            .lines()
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(str::to_owned)
+            .collect::<Arc<[String]>>();
+        (!tokens.is_empty()).then_some(Self(tokens))
+    }
+
+    pub fn contains(&self, token: &str) -> bool {
+        self.0.iter().any(|master_token| master_token == token)
+    }
+
+    /// Warnings about tokens that are short or easy to guess
+    pub fn weaknesses(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(index, token)| {
+                let number = index + 1;
+                let length = token.chars().count();
+                if length < MIN_MASTER_TOKEN_LENGTH {
+                    return Some(format!(
+                        "Master token {number} is {length} characters long, use a random token of at least {MIN_MASTER_TOKEN_LENGTH}"
+                    ));
+                }
+                if is_repetitive(token) {
+                    return Some(format!(
+                        "Master token {number} repeats a few characters or a pattern, use a random token"
+                    ));
+                }
+                let bits = max_entropy(token);
+                (bits < MIN_MASTER_TOKEN_BITS).then(|| {
+                    format!(
+                        "Master token {number} has at most {bits:.0} bits of entropy with its length and characters, use a longer random token"
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// Tokens are secrets, only their amount is shown
+impl fmt::Debug for MasterTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MasterTokens({} redacted)", self.0.len())
+    }
+}
+
+/// Most bits of entropy the token can have, if every character was drawn at random from the
+/// classes of characters it uses: lowercase and uppercase letters, digits and everything else
+fn max_entropy(token: &str) -> f64 {
+    let uses = |is_class: fn(&char) -> bool| token.chars().any(|c| is_class(&c));
+    let alphabet: u32 = [
+        (uses(char::is_ascii_lowercase), 26),
+        (uses(char::is_ascii_uppercase), 26),
+        (uses(char::is_ascii_digit), 10),
+        (uses(|c| !c.is_ascii_alphanumeric()), 32),
+    ]
+    .into_iter()
+    .filter_map(|(used, size)| used.then_some(size))
+    .sum();
+    token.chars().count() as f64 * f64::from(alphabet.max(1)).log2()
 }
 
-fn default_arc_str() -> Arc<str> {
-    "".into()
+/// Whether the token consists of a few characters or repeats a pattern, like `aaaa` or `abcabc`
+fn is_repetitive(token: &str) -> bool {
+    let chars = token.chars().collect::<Vec<_>>();
+    let distinct = chars.iter().collect::<HashSet<_>>().len();
+    distinct < MIN_DISTINCT_CHARS
+        || (1..=chars.len() / 2)
+            .any(|period| chars.iter().skip(period).eq(&chars[..chars.len() - period]))
 }
@@ -0,0 +1,507 @@
+use std::{collections::HashSet, fmt, ops::Range, path::PathBuf};
+
+use chrono::Duration;
+use croner::Cron;
+use toml_edit::{ImDocument, Item, Table};
+
+use serde_path_to_error::Segment;
+
+use super::{
+    domains::MIN_ID_LENGTH,
+    layers::{apply_env_overrides, ConfigSources, EnvOverride, ValueSource},
+    routes, ServiceConfig,
+};
+
+/// Longest period of durations, longer ones are a mistake and overflow date arithmetic
+const MAX_PERIOD: Duration = Duration::days(100 * 365);
+
+/// Problem with the config, at a key if it's about one
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    /// Dotted path of the key, e.g. `ip_recording.retention_period`
+    pub key: Option<String>,
+    /// 1-based line of the key or syntax error in the file, if it's known
+    pub line: Option<usize>,
+    /// Environment variable the key is set by
+    pub env_var: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(var) = &self.env_var {
+            write!(f, "env {var}")?;
+        } else if let Some(line) = &self.line {
+            write!(f, "line {line}")?;
+        }
+        match (&self.key, self.env_var.is_some() || self.line.is_some()) {
+            (Some(key), true) => write!(f, ", `{key}`: ")?,
+            (Some(key), false) => write!(f, "`{key}`: ")?,
+            (None, true) => f.write_str(": ")?,
+            (None, false) => {}
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// All problems found in the config
+#[derive(Debug, Clone)]
+pub struct ConfigErrors {
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid config, {} problem(s):", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n  {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+/// Parses the config file with the `SAYLESS_CONFIG__*` variables of the environment applied on top of
+/// it. Unknown keys are rejected, then the values and the rules between them are checked.
+pub fn parse_config(
+    file: Option<PathBuf>,
+    source: &str,
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Result<ServiceConfig, ConfigErrors> {
+    let mut table: toml::Table = source.parse().map_err(|e: toml::de::Error| ConfigErrors {
+        problems: vec![ConfigProblem {
+            key: None,
+            line: e.span().map(|span| line_of(source, span.start)),
+            env_var: None,
+            message: e.message().to_owned(),
+        }],
+    })?;
+    let locator = Locator {
+        source,
+        document: ImDocument::parse(source).ok(),
+        overrides: apply_env_overrides(&mut table, env).map_err(|errors| ConfigErrors {
+            problems: errors
+                .into_iter()
+                .map(|(var, message)| ConfigProblem {
+                    key: None,
+                    line: None,
+                    env_var: Some(var),
+                    message,
+                })
+                .collect(),
+        })?,
+    };
+
+    // Keys that can't be read are left out one at a time, so the ones after them are read too.
+    // The rules between values are only checked once everything can be read.
+    let mut readable = table.clone();
+    let mut removed = Vec::new();
+    let mut problems = Vec::new();
+    let config = loop {
+        match serde_path_to_error::deserialize(toml::Value::Table(readable.clone())) {
+            Ok(config) => break Some(config),
+            Err(e) => {
+                let (path, message) = error_path(e);
+                // A required key that was left out is already reported, its table is left out too
+                let left_out = message
+                    .strip_prefix("missing field `")
+                    .and_then(|rest| rest.split_once('`'))
+                    .is_some_and(|(missing, _)| {
+                        removed.iter().any(|removed: &Vec<String>| {
+                            removed.split_last().is_some_and(|(key, parent)| {
+                                key == missing && parent == path.as_slice()
+                            })
+                        })
+                    });
+                if !left_out {
+                    problems.push(locator.problem(&path.join("."), message));
+                }
+                match remove_enclosing_key(&mut readable, path) {
+                    Some(path) => removed.push(path),
+                    None => break None,
+                }
+            }
+        }
+    };
+    let mut config: ServiceConfig = match config {
+        Some(config) if problems.is_empty() => config,
+        _ => return Err(ConfigErrors { problems }),
+    };
+
+    let mut checker = Checker {
+        locator: &locator,
+        table: &table,
+        problems: Vec::new(),
+    };
+    checker.check(&config);
+    if !checker.problems.is_empty() {
+        return Err(ConfigErrors {
+            problems: checker.problems,
+        });
+    }
+
+    config.sources = ConfigSources::collect(file, &table, |key| locator.source_of(key));
+    Ok(config)
+}
+
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+/// Finds where keys are set, in the file or the environment
+struct Locator<'a> {
+    source: &'a str,
+    document: Option<ImDocument<&'a str>>,
+    overrides: Vec<EnvOverride>,
+}
+
+impl Locator<'_> {
+    fn env_var(&self, key: &str) -> Option<&str> {
+        self.overrides
+            .iter()
+            .rev()
+            .find(|env_override| env_override.covers(key))
+            .map(|env_override| env_override.var.as_str())
+    }
+
+    fn line(&self, key: &str) -> Option<usize> {
+        self.document
+            .as_ref()
+            .and_then(|document| key_span(document, key))
+            .map(|span| line_of(self.source, span.start))
+    }
+
+    fn source_of(&self, key: &str) -> ValueSource {
+        match self.env_var(key) {
+            Some(var) => ValueSource::Env {
+                var: var.to_owned(),
+            },
+            None => ValueSource::File {
+                line: self.line(key),
+            },
+        }
+    }
+
+    /// Problem with the key, `domains.1.id_length` points at the second domain
+    fn problem(&self, key: &str, message: String) -> ConfigProblem {
+        let env_var = self.env_var(key).map(str::to_owned);
+        ConfigProblem {
+            key: (!key.is_empty()).then(|| key.to_owned()),
+            line: env_var.is_none().then(|| self.line(key)).flatten(),
+            env_var,
+            message,
+        }
+    }
+}
+
+/// Path of the key a deserialization error is about, and the error message
+fn error_path(e: serde_path_to_error::Error<toml::de::Error>) -> (Vec<String>, String) {
+    let mut path = e
+        .path()
+        .iter()
+        .map(|segment| match segment {
+            Segment::Seq { index } => index.to_string(),
+            Segment::Map { key } => key.clone(),
+            Segment::Enum { variant } => variant.clone(),
+            Segment::Unknown => "?".to_owned(),
+        })
+        .collect::<Vec<_>>();
+    let message = e.into_inner().message().to_owned();
+    // The path may end at the table containing an unknown key rather than at the key
+    if let Some(unknown) = message
+        .strip_prefix("unknown field `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(unknown, _)| unknown)
+    {
+        if path.is_empty() || path == ["."] {
+            path = vec![unknown.to_owned()];
+        } else if path.last().map(String::as_str) != Some(unknown) {
+            path.push(unknown.to_owned());
+        }
+    }
+    (path, message)
+}
+
+/// Removes the key at the path, or the closest table key containing it if it's an array element.
+/// Returns the path of the removed key, `None` if there is none.
+fn remove_enclosing_key(table: &mut toml::Table, mut path: Vec<String>) -> Option<Vec<String>> {
+    while !path.is_empty() {
+        if remove_key(table, &path) {
+            return Some(path);
+        }
+        path.pop();
+    }
+    None
+}
+
+/// Removes the key at the path, `false` if it isn't a key of a table
+fn remove_key(table: &mut toml::Table, path: &[String]) -> bool {
+    match path {
+        [] => false,
+        [key] => table.remove(key).is_some(),
+        [key, rest @ ..] => table
+            .get_mut(key)
+            .is_some_and(|value| remove_nested_key(value, rest)),
+    }
+}
+
+fn remove_nested_key(value: &mut toml::Value, path: &[String]) -> bool {
+    match (value, path) {
+        (toml::Value::Table(table), path) => remove_key(table, path),
+        (toml::Value::Array(array), [index, rest @ ..]) if !rest.is_empty() => index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| array.get_mut(index))
+            .is_some_and(|value| remove_nested_key(value, rest)),
+        _ => false,
+    }
+}
+
+struct Checker<'a> {
+    locator: &'a Locator<'a>,
+    table: &'a toml::Table,
+    problems: Vec<ConfigProblem>,
+}
+
+impl Checker<'_> {
+    fn problem(&mut self, key: &str, message: String) {
+        self.problems.push(self.locator.problem(key, message));
+    }
+
+    fn check(&mut self, config: &ServiceConfig) {
+        if config.max_bulk_links == 0 {
+            self.problem("max_bulk_links", "Must be at least 1".to_owned());
+        }
+        if self.table.contains_key("max_strikes") && config.ip_recording.is_none() {
+            self.problem(
+                "max_strikes",
+                "Strikes are only checked with IP recording, enable `[ip_recording]` or remove it"
+                    .to_owned(),
+            );
+        }
+        if self.table.contains_key("strikes") && config.ip_recording.is_none() {
+            self.problem(
+                "strikes",
+                "Strikes are only checked with IP recording, enable `[ip_recording]` or remove it"
+                    .to_owned(),
+            );
+        }
+        if let Some(decay_period) = config.strikes.decay_period {
+            self.check_period("strikes.decay_period", decay_period);
+        }
+        if let Some(ban_duration) = config.strikes.ban_duration {
+            self.check_period("strikes.ban_duration", ban_duration);
+            self.check_period("strikes.max_ban_duration", config.strikes.max_ban_duration);
+        }
+        if !(config.strikes.ban_escalation.is_finite() && config.strikes.ban_escalation >= 1.0) {
+            self.problem("strikes.ban_escalation", "Must be at least 1".to_owned());
+        }
+
+        for (key, prefix) in [
+            ("routes.link_prefix", &config.routes.link_prefix),
+            ("routes.api_prefix", &config.routes.api_prefix),
+        ] {
+            if !routes::is_valid_prefix(prefix) {
+                self.problem(
+                    key,
+                    format!(
+                        "Route prefix `{prefix}` must be empty or start with `/`, and must not end with `/`"
+                    ),
+                );
+            }
+        }
+
+        if let Some(frontend) = &config.frontend {
+            if !routes::is_valid_prefix(&frontend.path) {
+                self.problem(
+                    "frontend.path",
+                    format!(
+                        "Frontend path `{}` must be empty or start with `/`, and must not end with `/`",
+                        frontend.path
+                    ),
+                );
+            }
+            let shadowed = if frontend.path.is_empty() {
+                config.routes.link_prefix.is_empty()
+            } else {
+                config
+                    .routes
+                    .link_prefixes()
+                    .into_iter()
+                    .chain(config.routes.api_prefixes())
+                    .any(|prefix| prefix == frontend.path)
+            };
+            if shadowed {
+                self.problem(
+                    "frontend.path",
+                    format!(
+                        "Frontend path `{}` must differ from the route prefixes, and can't be empty if short links are served at the root",
+                        frontend.path
+                    ),
+                );
+            }
+            if !frontend.assets_dir.join("index.html").is_file() {
+                self.problem(
+                    "frontend.assets_dir",
+                    format!(
+                        "`{}` must be a directory containing `index.html`",
+                        frontend.assets_dir.display()
+                    ),
+                );
+            }
+        }
+
+        let mut hosts = HashSet::new();
+        for (index, domain) in config.domains.iter().enumerate() {
+            if domain.id_length < MIN_ID_LENGTH {
+                self.problem(
+                    &format!("domains.{index}.id_length"),
+                    format!(
+                        "Link id length of domain `{}` must be at least {MIN_ID_LENGTH}",
+                        domain.host
+                    ),
+                );
+            }
+            if !hosts.insert(domain.host.to_ascii_lowercase()) {
+                self.problem(
+                    &format!("domains.{index}.host"),
+                    format!("Domain `{}` is configured more than once", domain.host),
+                );
+            }
+        }
+
+        if let Some(ip_recording) = &config.ip_recording {
+            self.check_period(
+                "ip_recording.retention_period",
+                ip_recording.retention_period,
+            );
+            self.check_cron(
+                "ip_recording.retention_check_period",
+                &ip_recording.retention_check_period,
+            );
+        }
+
+        self.check_period(
+            "audit_log.retention_period",
+            config.audit_log.retention_period,
+        );
+        self.check_cron(
+            "audit_log.retention_check_period",
+            &config.audit_log.retention_check_period,
+        );
+
+        for (key, schedule) in [
+            (
+                "maintenance.expired_tokens",
+                &config.maintenance.expired_tokens,
+            ),
+            (
+                "maintenance.expired_links",
+                &config.maintenance.expired_links,
+            ),
+            ("maintenance.strike_decay", &config.maintenance.strike_decay),
+            (
+                "maintenance.orphaned_origins",
+                &config.maintenance.orphaned_origins,
+            ),
+        ] {
+            if let Some(schedule) = schedule {
+                self.check_cron(key, schedule);
+            }
+        }
+
+        if config.link_passwords.max_failed_attempts > 0 {
+            self.check_period(
+                "link_passwords.lockout_period",
+                config.link_passwords.lockout_period,
+            );
+        }
+
+        let max_age = config.redirects.permanent_cache_max_age;
+        if max_age < Duration::zero() || max_age.num_seconds() > i64::from(u32::MAX) {
+            self.problem(
+                "redirects.permanent_cache_max_age",
+                format!("Must be between 0 and {} seconds", u32::MAX),
+            );
+        }
+
+        if config.redirect_cache.capacity > 0 {
+            self.check_period("redirect_cache.ttl", config.redirect_cache.ttl);
+        }
+
+        let mut addresses = HashSet::new();
+        for (index, address) in config.server.listen.iter().enumerate() {
+            if !addresses.insert(address) {
+                self.problem(
+                    &format!("server.listen.{index}"),
+                    format!("Address `{address}` is listed more than once"),
+                );
+            }
+        }
+        if config.server.unix_socket_mode > 0o777 {
+            self.problem(
+                "server.unix_socket_mode",
+                format!(
+                    "Mode {:#o} must be at most 0o777",
+                    config.server.unix_socket_mode
+                ),
+            );
+        }
+        if let Some(tls) = &config.server.tls {
+            for (key, path) in [("server.tls.cert", &tls.cert), ("server.tls.key", &tls.key)] {
+                if !path.is_file() {
+                    self.problem(key, format!("`{}` must be a file", path.display()));
+                }
+            }
+        }
+    }
+
+    fn check_period(&mut self, key: &str, duration: Duration) {
+        if duration <= Duration::zero() {
+            self.problem(key, "Duration must be longer than 0".to_owned());
+        } else if duration > MAX_PERIOD {
+            self.problem(key, "Duration must be at most 100 years".to_owned());
+        }
+    }
+
+    fn check_cron(&mut self, key: &str, expression: &str) {
+        // Parsed the same way as by the scheduler
+        if let Err(e) = Cron::new(expression)
+            .with_seconds_required()
+            .with_dom_and_dow()
+            .parse()
+        {
+            self.problem(key, format!("Invalid cron expression `{expression}`: {e}"));
+        }
+    }
+}
+
+/// Span of the key in the document, or of the closest table containing it if it isn't set
+fn key_span(document: &ImDocument<&str>, key: &str) -> Option<Range<usize>> {
+    let mut table: &Table = document.as_table();
+    let mut span = None;
+    let mut parts = key.split('.');
+    while let Some(part) = parts.next() {
+        let Some(item) = table.get(part) else {
+            return span;
+        };
+        span = item.span().or(span);
+        table = match item {
+            Item::Table(nested) => nested,
+            Item::ArrayOfTables(array) => {
+                let Some(nested) = parts
+                    .next()
+                    .and_then(|index| index.parse().ok())
+                    .and_then(|index| array.get(index))
+                else {
+                    return span;
+                };
+                nested
+            }
+            _ => return span,
+        };
+        span = table.span().or(span);
+    }
+    span
+}
@@ -0,0 +1,379 @@
+use std::net::IpAddr;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{MySql, MySqlConnection, Pool};
+
+use crate::service_config::{strikes::StrikesConfig, ServiceConfig};
+
+#[derive(Debug)]
+struct StrikesQuery {
+    amount: u16,
+    updated_at: DateTime<Utc>,
+    banned_until: Option<DateTime<Utc>>,
+    bans: u16,
+}
+
+#[derive(Debug)]
+struct StrikesEntryQuery {
+    origin: Vec<u8>,
+    amount: u16,
+    updated_at: DateTime<Utc>,
+    banned_until: Option<DateTime<Utc>>,
+    bans: u16,
+}
+
+/// Latest time the `banned_until` column can hold, the end of MySQL `TIMESTAMP`
+const MAX_TIMESTAMP: i64 = i32::MAX as i64;
+
+/// End of a ban that lasts `duration` from `start`, cut short to what can be stored
+fn ban_end(start: DateTime<Utc>, duration: Option<Duration>) -> DateTime<Utc> {
+    let latest = DateTime::from_timestamp(MAX_TIMESTAMP, 0).unwrap_or(DateTime::<Utc>::MAX_UTC);
+    duration
+        .and_then(|duration| start.checked_add_signed(duration))
+        .map_or(latest, |end| end.min(latest))
+}
+
+/// Strikes of an IP with the configured expiry applied
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StrikeStatus {
+    pub amount: u16,
+    /// Whether the IP can't create links
+    pub banned: bool,
+    /// When the ban lifts, `None` if it doesn't lift on its own
+    pub banned_until: Option<DateTime<Utc>>,
+}
+
+/// Row of the strikes table. `updated_at` is the time expiry of the strikes counts from.
+#[derive(Debug, Clone, Copy)]
+struct StrikeRecord {
+    amount: u16,
+    updated_at: DateTime<Utc>,
+    banned_until: Option<DateTime<Utc>>,
+    bans: u16,
+}
+
+impl StrikeRecord {
+    fn empty(now: DateTime<Utc>) -> Self {
+        Self {
+            amount: 0,
+            updated_at: now,
+            banned_until: None,
+            bans: 0,
+        }
+    }
+
+    /// The record with its expired strikes removed
+    fn decayed(self, config: &StrikesConfig, now: DateTime<Utc>) -> Self {
+        let (amount, updated_at) = config.decay(self.amount, self.updated_at, now);
+        Self {
+            amount,
+            updated_at,
+            ..self
+        }
+    }
+
+    fn has_active_ban(&self, now: DateTime<Utc>) -> bool {
+        self.banned_until.is_some_and(|until| until > now)
+    }
+
+    fn status(&self, config: &ServiceConfig, now: DateTime<Utc>) -> StrikeStatus {
+        let (banned, banned_until) = if self.has_active_ban(now) {
+            (true, self.banned_until)
+        } else if (config.strikes.ban_duration.is_none() || self.banned_until.is_none())
+            && self.amount >= config.max_strikes
+        {
+            // Lifts once enough strikes have expired to go below the maximum. IPs that reached it
+            // without a ban being recorded, before bans had a duration, are banned the same way.
+            let excess = i32::from(self.amount) - i32::from(config.max_strikes) + 1;
+            let lifts_at = config
+                .strikes
+                .decay_period
+                .map(|period| ban_end(self.updated_at, period.checked_mul(excess)));
+            (true, lifts_at)
+        } else {
+            (false, None)
+        };
+        StrikeStatus {
+            amount: self.amount,
+            banned,
+            banned_until,
+        }
+    }
+}
+
+fn serialize_origin(ip: IpAddr) -> Result<Vec<u8>, StatusCode> {
+    bincode::serialize(&ip).map_err(|e| {
+        log::error!("Error serializing origin ip {ip}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+async fn query_record(
+    conn: &mut MySqlConnection,
+    config: &StrikesConfig,
+    ip: IpAddr,
+    now: DateTime<Utc>,
+) -> Result<StrikeRecord, StatusCode> {
+    let origin = serialize_origin(ip)?;
+    Ok(sqlx::query_as!(
+        StrikesQuery,
+        "SELECT amount, updated_at, banned_until, bans FROM strikes WHERE origin = ?",
+        origin
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        log::error!("Error looking up strikes for {ip}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .map_or(
+        StrikeRecord::empty(now),
+        |StrikesQuery {
+             amount,
+             updated_at,
+             banned_until,
+             bans,
+         }| StrikeRecord {
+            amount,
+            updated_at,
+            banned_until,
+            bans,
+        },
+    )
+    .decayed(config, now))
+}
+
+/// Replaces the record of the IP. Records without strikes or an active ban are removed.
+async fn replace_record(
+    conn: &mut MySqlConnection,
+    ip: IpAddr,
+    record: &StrikeRecord,
+    now: DateTime<Utc>,
+) -> Result<(), StatusCode> {
+    let origin = serialize_origin(ip)?;
+
+    sqlx::query!("DELETE FROM strikes WHERE origin = ?", &origin)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            log::error!("Error removing strikes for {ip}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if record.amount > 0 || record.has_active_ban(now) {
+        sqlx::query!(
+            r#"
+            INSERT INTO strikes (origin, amount, updated_at, banned_until, bans)
+            values (?, ?, ?, ?, ?)
+            "#,
+            &origin,
+            record.amount,
+            record.updated_at,
+            record.banned_until,
+            record.bans
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            log::error!("Error recording strikes for {ip}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+    Ok(())
+}
+
+/// Changes the amount of strikes of the IP. New strikes restart their expiry, and reaching
+/// `max_strikes` bans the IP if bans have a duration. Removing all strikes lifts the ban.
+async fn update_strikes(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    ip: IpAddr,
+    amount: impl FnOnce(u16) -> u16,
+) -> Result<StrikeStatus, StatusCode> {
+    let mut tx = db.begin().await.map_err(|e| {
+        log::error!("Error starting strikes transaction: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let now = Utc::now();
+    let mut record = query_record(&mut tx, &config.strikes, ip, now).await?;
+    let previous = record.amount;
+    record.amount = amount(previous);
+    if record.amount == 0 {
+        record = StrikeRecord::empty(now);
+    } else if record.amount > previous {
+        record.updated_at = now;
+        if record.amount >= config.max_strikes
+            && !record.has_active_ban(now)
+            && let Some(duration) = config.strikes.ban_duration(record.bans)
+        {
+            record.banned_until = Some(ban_end(now, Some(duration)));
+            record.bans = record.bans.saturating_add(1);
+        }
+    }
+    replace_record(&mut tx, ip, &record, now).await?;
+
+    tx.commit().await.map_err(|e| {
+        log::error!("Error committing strikes transaction: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(record.status(config, now))
+}
+
+/// Strikes recorded for the IP and whether it's banned
+#[tracing::instrument(skip_all)]
+pub async fn get_strikes(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    ip: IpAddr,
+) -> Result<StrikeStatus, StatusCode> {
+    let mut conn = db.acquire().await.map_err(|e| {
+        log::error!("Error acquiring connection for strikes lookup: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let now = Utc::now();
+    Ok(query_record(&mut conn, &config.strikes, ip, now)
+        .await?
+        .status(config, now))
+}
+
+/// Replaces the amount of strikes recorded for the IP, 0 removes them along with any ban
+#[tracing::instrument(skip_all)]
+pub async fn set_strikes(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    ip: IpAddr,
+    amount: u16,
+) -> Result<StrikeStatus, StatusCode> {
+    update_strikes(db, config, ip, |_| amount).await
+}
+
+/// Adds strikes to the ones already recorded for the IP
+#[tracing::instrument(skip_all)]
+pub async fn add_strikes(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    ip: IpAddr,
+    amount: u16,
+) -> Result<StrikeStatus, StatusCode> {
+    update_strikes(db, config, ip, |previous| previous.saturating_add(amount)).await
+}
+
+/// Removes expired strikes, or takes one strike from every IP if strikes don't expire, and
+/// removes IPs left without strikes or an active ban. Returns how many IPs lost strikes.
+#[tracing::instrument(skip_all)]
+pub async fn decay_strikes(db: &Pool<MySql>, config: &StrikesConfig) -> Result<u64, sqlx::Error> {
+    let now = Utc::now();
+    let mut tx = db.begin().await?;
+    let decayed = match config.decay_period {
+        Some(period) => {
+            let period = period.num_seconds().max(1);
+            // `updated_at` moves by whole periods only, so partly elapsed ones still count
+            sqlx::query!(
+                r#"
+                UPDATE strikes
+                SET amount = amount - LEAST(amount, FLOOR(TIMESTAMPDIFF(SECOND, updated_at, ?) / ?)),
+                updated_at = updated_at + INTERVAL FLOOR(TIMESTAMPDIFF(SECOND, updated_at, ?) / ?) * ? SECOND
+                WHERE amount > 0 AND TIMESTAMPDIFF(SECOND, updated_at, ?) >= ?
+                "#,
+                now,
+                period,
+                now,
+                period,
+                period,
+                now,
+                period
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+        }
+        None => sqlx::query!("UPDATE strikes SET amount = amount - 1 WHERE amount > 0")
+            .execute(&mut *tx)
+            .await?
+            .rows_affected(),
+    };
+    sqlx::query!(
+        "DELETE FROM strikes WHERE amount = 0 AND (banned_until IS NULL OR banned_until <= ?)",
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(decayed)
+}
+
+/// IPs with strikes or a ban out of the IPs that were looked at
+#[derive(Debug)]
+pub struct StrikesPage {
+    pub strikes: Vec<(IpAddr, StrikeStatus)>,
+    /// Last IP that was looked at, if there are more
+    pub next_cursor: Option<IpAddr>,
+}
+
+/// Looks at up to `limit` IPs after the IP `cursor`, IPv4 before IPv6, and returns the ones with
+/// strikes or a ban
+#[tracing::instrument(skip_all)]
+pub async fn list_strikes(
+    db: &Pool<MySql>,
+    config: &ServiceConfig,
+    cursor: Option<IpAddr>,
+    limit: u32,
+) -> Result<StrikesPage, StatusCode> {
+    let cursor = cursor
+        .map(|ip| bincode::serialize(&ip))
+        .transpose()
+        .map_err(|e| {
+            log::error!("Error serializing strikes cursor: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    // One extra row tells whether there is a next page. Serialized IPs sort by version, then
+    // by address.
+    let entries = sqlx::query_as!(
+        StrikesEntryQuery,
+        r#"
+        SELECT origin, amount, updated_at, banned_until, bans
+        FROM strikes
+        WHERE (? IS NULL OR origin > ?)
+        ORDER BY origin
+        LIMIT ?
+        "#,
+        cursor,
+        cursor,
+        limit + 1
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        log::error!("Error listing strikes: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let more = entries.len() > limit as usize;
+
+    let now = Utc::now();
+    let mut strikes = Vec::new();
+    let mut last = None;
+    for entry in entries.into_iter().take(limit as usize) {
+        let ip = bincode::deserialize(&entry.origin).map_err(|e| {
+            log::error!("Error deserializing origin of strikes: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        last = Some(ip);
+        let status = StrikeRecord {
+            amount: entry.amount,
+            updated_at: entry.updated_at,
+            banned_until: entry.banned_until,
+            bans: entry.bans,
+        }
+        .decayed(&config.strikes, now)
+        .status(config, now);
+        if status.amount > 0 || status.banned {
+            strikes.push((ip, status));
+        }
+    }
+    Ok(StrikesPage {
+        strikes,
+        next_cursor: last.filter(|_| more),
+    })
+}
@@ -1,77 +1,309 @@
 use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use rand::prelude::*;
 use sqlx::{MySql, Pool};
 
-use super::json_schemas::token_permissions::TokenPermissions;
+use super::{
+    base58::Base58Chars,
+    json_schemas::{create_token_params::CreateTokenParams, token_permissions::TokenPermissions},
+    service_config::token::MasterTokens,
+};
 
-#[derive(Debug)]
-struct TokenExistenceCheck {
-    tok_exists: bool,
+/// Whether the token hasn't expired and has all the permissions. 401 if it doesn't exist.
+#[tracing::instrument(skip_all)]
+pub async fn check_permission(
+    db: &Pool<MySql>,
+    master_tokens: &MasterTokens,
+    token: &str,
+    perms: TokenPermissions,
+) -> Result<bool, StatusCode> {
+    Ok(identify_token(db, master_tokens, token)
+        .await?
+        .has_permissions(perms))
+}
+
+/// Who a supplied token belongs to
+#[derive(Debug, Clone, Copy)]
+pub enum TokenIdentity {
+    Master,
+    Token { id: u64, perms: TokenPermissions },
+}
+
+impl TokenIdentity {
+    /// Id of the token, master token doesn't have one
+    pub fn id(&self) -> Option<u64> {
+        match self {
+            Self::Master => None,
+            Self::Token { id, .. } => Some(*id),
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        match self {
+            Self::Master => true,
+            Self::Token { perms, .. } => perms.admin_perm,
+        }
+    }
+
+    /// Admins can create links without the link creation permission
+    pub fn can_create_links(&self) -> bool {
+        match self {
+            Self::Master => true,
+            Self::Token { perms, .. } => perms.admin_perm || perms.create_link_perm,
+        }
+    }
+
+    pub fn can_view_ips(&self) -> bool {
+        match self {
+            Self::Master => true,
+            Self::Token { perms, .. } => perms.view_ips_perm,
+        }
+    }
+
+    /// Whether the token has every one of the permissions
+    pub fn has_permissions(
+        &self,
+        TokenPermissions {
+            admin_perm,
+            create_link_perm,
+            view_ips_perm,
+        }: TokenPermissions,
+    ) -> bool {
+        (!admin_perm || self.is_admin())
+            && (!create_link_perm || self.can_create_links())
+            && (!view_ips_perm || self.can_view_ips())
+    }
 }
 
 #[derive(Debug)]
-struct Token {
-    #[allow(dead_code)]
-    token: String,
+struct TokenIdentityQuery {
+    id: u64,
+    admin_perm: bool,
+    create_link_perm: bool,
+    view_ips_perm: bool,
 }
 
-pub async fn check_permission(
+/// Looks up a token that hasn't expired yet
+#[tracing::instrument(skip_all)]
+pub async fn identify_token(
     db: &Pool<MySql>,
-    master_token: &str,
+    master_tokens: &MasterTokens,
     token: &str,
-    TokenPermissions {
-        admin_perm: _,
+) -> Result<TokenIdentity, StatusCode> {
+    if master_tokens.contains(token) {
+        return Ok(TokenIdentity::Master);
+    }
+    let TokenIdentityQuery {
+        id,
+        admin_perm,
         create_link_perm,
         view_ips_perm,
-    }: TokenPermissions,
-) -> Result<bool, StatusCode> {
-    if token == master_token {
-        return Ok(true);
-    }
-    let tok_exists = sqlx::query_as!(
-        TokenExistenceCheck,
+    } = sqlx::query_as!(
+        TokenIdentityQuery,
         r#"
-        SELECT CASE WHEN EXISTS (
-            SELECT *
-            FROM tokens
-            WHERE token = ? AND expires_at > CURRENT_TIMESTAMP
-        )
-        THEN TRUE
-        ELSE FALSE
-        END AS `tok_exists: _`
+        SELECT
+            id,
+            admin_perm AS `admin_perm: _`,
+            create_link_perm AS `create_link_perm: _`,
+            view_ips_perm AS `view_ips_perm: _`
+        FROM tokens
+        WHERE token = ? AND expires_at > CURRENT_TIMESTAMP
         "#,
         token,
     )
-    .fetch_one(db)
+    .fetch_optional(db)
     .await
     .map_err(|e| {
-        log::error!("Error checking if token `{token}` exists: {e}");
+        log::error!("Error looking up token `{token}`: {e}");
         StatusCode::INTERNAL_SERVER_ERROR
     })?
-    .tok_exists;
+    .ok_or(StatusCode::UNAUTHORIZED)?;
 
-    if !tok_exists {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
+    Ok(TokenIdentity::Token {
+        id,
+        perms: TokenPermissions {
+            admin_perm,
+            create_link_perm,
+            view_ips_perm,
+        },
+    })
+}
+
+/// Generates and stores a new token with the requested permissions. Returns the id and the token.
+#[tracing::instrument(skip_all)]
+pub async fn insert_token(
+    db: &Pool<MySql>,
+    params: CreateTokenParams,
+) -> Result<(u64, String), StatusCode> {
+    let rng = StdRng::from_os_rng();
+    let new_token: String = rng.sample_iter(Base58Chars).take(44).collect();
+
+    let result = match params.expires_at {
+        None => sqlx::query!(
+            r#"
+                INSERT INTO tokens (
+                    token,
+                    admin_perm,
+                    create_link_perm,
+                    view_ips_perm
+                ) values (?, ?, ?, ?)
+                "#,
+            &new_token,
+            params.perms.admin_perm,
+            params.perms.create_link_perm,
+            params.perms.view_ips_perm
+        )
+        .execute(db)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to insert new token: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+        Some(expiration_date) => sqlx::query!(
+            r#"
+                INSERT INTO tokens (
+                    token,
+                    expires_at,
+                    admin_perm,
+                    create_link_perm,
+                    view_ips_perm
+                ) values (?, ?, ?, ?, ?)
+                "#,
+            &new_token,
+            expiration_date,
+            params.perms.admin_perm,
+            params.perms.create_link_perm,
+            params.perms.view_ips_perm
+        )
+        .execute(db)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to insert new token: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+    };
 
-    Ok(sqlx::query_as!(
-        Token,
-        r#"SELECT
-            token
+    Ok((result.last_insert_id(), new_token))
+}
+
+#[derive(Debug)]
+struct TokenIdQuery {
+    id: u64,
+}
+
+/// Expires the token right away. Returns the id of the token, if it exists.
+#[tracing::instrument(skip_all)]
+pub async fn expire_token(db: &Pool<MySql>, token: &str) -> Result<Option<u64>, StatusCode> {
+    let Some(TokenIdQuery { id }) =
+        sqlx::query_as!(TokenIdQuery, "SELECT id FROM tokens WHERE token = ?", token)
+            .fetch_optional(db)
+            .await
+            .map_err(|e| {
+                log::error!("Error looking up token to expire: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+    else {
+        return Ok(None);
+    };
+    sqlx::query!(
+        "UPDATE tokens SET expires_at = LEAST(expires_at, CURRENT_TIMESTAMP) WHERE id = ?",
+        id
+    )
+    .execute(db)
+    .await
+    .map_err(|e| {
+        log::error!("Error setting expiration date: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Some(id))
+}
+
+/// Expires the token with the id right away. Returns whether the token exists.
+#[tracing::instrument(skip_all)]
+pub async fn expire_token_by_id(db: &Pool<MySql>, id: u64) -> Result<bool, StatusCode> {
+    Ok(sqlx::query!(
+        "UPDATE tokens SET expires_at = LEAST(expires_at, CURRENT_TIMESTAMP) WHERE id = ?",
+        id
+    )
+    .execute(db)
+    .await
+    .map_err(|e| {
+        log::error!("Error setting expiration date of token {id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected()
+        > 0)
+}
+
+/// Deletes tokens that have expired or were revoked. Links they own are left without an owner, so
+/// their ids don't refer to tokens that don't exist. Returns how many were deleted.
+#[tracing::instrument(skip_all)]
+pub async fn purge_expired_tokens(db: &Pool<MySql>) -> Result<u64, sqlx::Error> {
+    let now = Utc::now();
+    let mut tx = db.begin().await?;
+    sqlx::query!(
+        r#"
+        UPDATE links
+        SET created_by_token = NULL
+        WHERE created_by_token IN (SELECT id FROM tokens WHERE expires_at <= ?)
+        "#,
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+    let deleted = sqlx::query!("DELETE FROM tokens WHERE expires_at <= ?", now)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Stored token, without the token itself
+#[derive(Debug)]
+pub struct TokenEntry {
+    pub id: u64,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub admin_perm: bool,
+    pub create_link_perm: bool,
+    pub view_ips_perm: bool,
+}
+
+/// Lists up to `limit` stored tokens after the id `cursor`, oldest first. Expired tokens are only
+/// included if requested.
+#[tracing::instrument(skip_all)]
+pub async fn list_tokens(
+    db: &Pool<MySql>,
+    include_expired: bool,
+    cursor: Option<u64>,
+    limit: u32,
+) -> Result<Vec<TokenEntry>, StatusCode> {
+    sqlx::query_as!(
+        TokenEntry,
+        r#"
+        SELECT
+            id,
+            created_at,
+            expires_at,
+            admin_perm AS `admin_perm: _`,
+            create_link_perm AS `create_link_perm: _`,
+            view_ips_perm AS `view_ips_perm: _`
         FROM tokens
-        WHERE token = ?
-        OR admin_perm = 1
-        AND (create_link_perm = 1 OR create_link_perm = ?)
-        AND (view_ips_perm = 1 OR view_ips_perm = ?);
+        WHERE (? OR expires_at > CURRENT_TIMESTAMP)
+        AND (? IS NULL OR id > ?)
+        ORDER BY id
+        LIMIT ?
         "#,
-        token,
-        create_link_perm,
-        view_ips_perm
+        include_expired,
+        cursor,
+        cursor,
+        limit
     )
-    .fetch_optional(db)
+    .fetch_all(db)
     .await
     .map_err(|e| {
-        log::error!("Error fetching permissions for token `{}`: {}", token, e);
+        log::error!("Error listing tokens: {e}");
         StatusCode::INTERNAL_SERVER_ERROR
-    })?
-    .is_some())
+    })
 }